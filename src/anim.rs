@@ -0,0 +1,364 @@
+//! SMIL animation elements, animate, animateTransform, animateMotion and set.
+//!
+//! An animation acts on the element it is inside, so either add it as a child of a
+//! Tag, or write it inside the TransWrap returned by the Tag's wrap.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::anim::{Animate, AnimateTransform, Fill, TransformType};
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     let mut card = Tag::rect(0, 0, 50, 70).wrap(&mut svg);
+//!     Animate::new("opacity")
+//!         .values(&[1, 0, 1])
+//!         .dur("2s")
+//!         .repeat_indefinite()
+//!         .write(&mut card)
+//!         .unwrap();
+//!     AnimateTransform::new(TransformType::Rotate)
+//!         .from("0 25 35")
+//!         .to("360 25 35")
+//!         .dur("4s")
+//!         .fill(Fill::Freeze)
+//!         .write(&mut card)
+//!         .unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r#"<rect x="0" y="0" width="50" height="70" >
+//!   <animate attributeName="opacity" values="1;0;1" dur="2s" repeatCount="indefinite" />
+//!   <animateTransform attributeName="transform" type="rotate" from="0 25 35" to="360 25 35" dur="4s" fill="freeze" />
+//! </rect>
+//! "#
+//! );
+//! ```
+
+use crate::args::SvgArg;
+use crate::doc::Node;
+use crate::path::PathD;
+use crate::tag::Tag;
+use crate::write::SvgWrite;
+use std::fmt;
+use std::fmt::Display;
+
+/// What happens when the animation ends
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Fill {
+    /// keep the last value
+    Freeze,
+    /// go back to the value before the animation
+    Remove,
+}
+
+impl Display for Fill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Fill::Freeze => write!(f, "freeze"),
+            Fill::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+/// How values are interpolated
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CalcMode {
+    Discrete,
+    Linear,
+    Paced,
+    Spline,
+}
+
+impl Display for CalcMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            CalcMode::Discrete => "discrete",
+            CalcMode::Linear => "linear",
+            CalcMode::Paced => "paced",
+            CalcMode::Spline => "spline",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The kind of transform an AnimateTransform changes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TransformType {
+    Translate,
+    Scale,
+    Rotate,
+    SkewX,
+    SkewY,
+}
+
+impl Display for TransformType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            TransformType::Translate => "translate",
+            TransformType::Scale => "scale",
+            TransformType::Rotate => "rotate",
+            TransformType::SkewX => "skewX",
+            TransformType::SkewY => "skewY",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+fn join<T: Display>(v: &[T], sep: &str) -> String {
+    v.iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join(sep)
+}
+
+/// Timing methods, shared by every animation element
+macro_rules! timing_impl {
+    ($t:ident) => {
+        impl $t {
+            pub fn id<T: Display>(mut self, id: T) -> Self {
+                self.0 = self.0.id(id);
+                self
+            }
+
+            /// Any other argument, such as attributeType or restart
+            pub fn arg<T: Display>(mut self, k: &str, v: T) -> Self {
+                self.0 = self.0.arg(k, v);
+                self
+            }
+
+            /// The length of one run, such as "2s" or "500ms"
+            pub fn dur<T: Display>(self, d: T) -> Self {
+                self.arg("dur", d)
+            }
+
+            /// When to start, such as "1s", "click" or "other.end"
+            pub fn begin<T: Display>(self, b: T) -> Self {
+                self.arg("begin", b)
+            }
+
+            pub fn end<T: Display>(self, e: T) -> Self {
+                self.arg("end", e)
+            }
+
+            pub fn repeat_count<T: Display>(self, n: T) -> Self {
+                self.arg("repeatCount", n)
+            }
+
+            pub fn repeat_indefinite(self) -> Self {
+                self.repeat_count("indefinite")
+            }
+
+            pub fn fill(self, f: Fill) -> Self {
+                self.arg("fill", f)
+            }
+
+            pub fn write<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
+                self.0.write(w)
+            }
+        }
+
+        impl From<$t> for Tag {
+            fn from(a: $t) -> Self {
+                a.0
+            }
+        }
+
+        impl From<$t> for Node {
+            fn from(a: $t) -> Self {
+                a.0.into()
+            }
+        }
+
+        impl Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+/// Value methods, for the elements that interpolate
+macro_rules! values_impl {
+    ($t:ident) => {
+        impl $t {
+            pub fn from<T: Display>(self, v: T) -> Self {
+                self.arg("from", v)
+            }
+
+            pub fn to<T: Display>(self, v: T) -> Self {
+                self.arg("to", v)
+            }
+
+            pub fn by<T: Display>(self, v: T) -> Self {
+                self.arg("by", v)
+            }
+
+            /// The values to pass through, in place of from and to
+            pub fn values<T: Display>(self, v: &[T]) -> Self {
+                self.arg("values", join(v, ";"))
+            }
+
+            /// When each value is reached, from 0 to 1
+            pub fn key_times(self, t: &[f64]) -> Self {
+                self.arg("keyTimes", join(t, ";"))
+            }
+
+            /// A bezier control pair (x1 y1 x2 y2) for each interval,
+            /// this also sets calcMode to spline
+            pub fn key_splines(self, s: &[(f64, f64, f64, f64)]) -> Self {
+                let v: Vec<String> = s
+                    .iter()
+                    .map(|(a, b, c, d)| format!("{} {} {} {}", a, b, c, d))
+                    .collect();
+                self.calc_mode(CalcMode::Spline)
+                    .arg("keySplines", v.join(";"))
+            }
+
+            pub fn calc_mode(self, c: CalcMode) -> Self {
+                self.arg("calcMode", c)
+            }
+
+            /// Add to the value instead of replacing it
+            pub fn additive(self) -> Self {
+                self.arg("additive", "sum")
+            }
+
+            /// Each repeat builds on the last
+            pub fn accumulate(self) -> Self {
+                self.arg("accumulate", "sum")
+            }
+        }
+    };
+}
+
+/// Animates one argument or style of its parent
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animate(Tag);
+
+impl Animate {
+    pub fn new<T: Display>(attribute: T) -> Self {
+        Animate(Tag::new("animate").arg("attributeName", attribute))
+    }
+}
+
+timing_impl!(Animate);
+values_impl!(Animate);
+
+/// Animates the transform of its parent
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimateTransform(Tag);
+
+impl AnimateTransform {
+    pub fn new(t: TransformType) -> Self {
+        AnimateTransform(
+            Tag::new("animateTransform")
+                .arg("attributeName", "transform")
+                .arg("type", t),
+        )
+    }
+}
+
+timing_impl!(AnimateTransform);
+values_impl!(AnimateTransform);
+
+/// Moves its parent along a path
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimateMotion(Tag);
+
+impl AnimateMotion {
+    pub fn new(p: PathD) -> Self {
+        AnimateMotion(Tag::new("animateMotion").arg("path", p))
+    }
+
+    /// Turn the parent to follow the direction of the path
+    pub fn rotate_auto(self) -> Self {
+        self.arg("rotate", "auto")
+    }
+
+    /// How far along the path each keyTime is, from 0 to 1
+    pub fn key_points(self, p: &[f64]) -> Self {
+        self.arg("keyPoints", join(p, ";"))
+    }
+}
+
+timing_impl!(AnimateMotion);
+values_impl!(AnimateMotion);
+
+/// Sets an argument or style of its parent, without interpolating
+#[derive(Clone, Debug, PartialEq)]
+pub struct Set(Tag);
+
+impl Set {
+    pub fn new<T: Display, V: Display>(attribute: T, to: V) -> Self {
+        Set(Tag::new("set")
+            .arg("attributeName", attribute)
+            .arg("to", to))
+    }
+}
+
+timing_impl!(Set);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::validate::Validator;
+    use crate::write::SvgFmt;
+
+    #[test]
+    pub fn test_anims_as_children() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 50, 50).unwrap();
+            let p = PathD::abs().m(0, 0).l(40, 0);
+            Tag::circle(0, 0, 5)
+                .child(
+                    AnimateMotion::new(p)
+                        .dur("3s")
+                        .key_times(&[0., 1.])
+                        .key_points(&[0., 1.])
+                        .rotate_auto(),
+                )
+                .child(
+                    Animate::new("r")
+                        .values(&[5, 8, 5])
+                        .key_times(&[0., 0.5, 1.])
+                        .key_splines(&[(0.5, 0., 0.5, 1.), (0.5, 0., 0.5, 1.)])
+                        .dur("1s")
+                        .id("pulse"),
+                )
+                .child(Set::new("fill", "red").begin("pulse.end"))
+                .write(&mut svg)
+                .unwrap();
+        }
+        assert!(v.report().is_ok());
+        assert!(s.contains(
+            r#"<animateMotion path="M 0 0 L 40 0 " dur="3s" keyTimes="0;1" keyPoints="0;1" rotate="auto" />"#
+        ));
+        assert!(s.contains(
+            r#"calcMode="spline" keySplines="0.5 0 0.5 1;0.5 0 0.5 1" dur="1s" id="pulse" />"#
+        ));
+        assert!(s.contains(r#"<set attributeName="fill" to="red" begin="pulse.end" />"#));
+    }
+
+    #[test]
+    pub fn test_fill_stays_an_arg_in_styles_mode() {
+        use crate::args::StyleMode;
+        use crate::format::Format;
+        let mut s = String::new();
+        {
+            let f = Format::default().style_mode(StyleMode::Styles);
+            let mut w = SvgFmt::with_format(&mut s, f);
+            Tag::rect(0, 0, 5, 5)
+                .arg("fill", "blue")
+                .child(Animate::new("opacity").fill(Fill::Freeze))
+                .child(Set::new("fill", "red").fill(Fill::Remove))
+                .write(&mut w)
+                .unwrap();
+        }
+        assert!(s.contains(r#"<rect x="0" y="0" width="5" height="5" style="fill:blue;" >"#));
+        assert!(s.contains(r#"<animate attributeName="opacity" fill="freeze" />"#));
+        assert!(s.contains(r#"fill="remove""#));
+        assert!(!s.contains("fill:freeze") && !s.contains("fill:remove"));
+    }
+}
@@ -15,11 +15,16 @@
 //! ```
 //!
 //! the SvgWrite methods (from mod write) accept an Args object.
+//!
+//! values are escaped when written, use raw_arg for values that are already valid xml.
 
+use crate::escape::escape_attr;
+use crate::transform::Transform;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::marker::Sized;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 enum AType {
     ARG,
@@ -34,11 +39,108 @@ struct Arg {
     k: String,
     v: String,
     tp: AType,
+    raw: bool,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl Arg {
+    fn value(&self) -> std::borrow::Cow<'_, str> {
+        match self.raw {
+            true => self.v.as_str().into(),
+            false => escape_attr(&self.v),
+        }
+    }
+}
+
+/// How style items are written, see Format::style_mode
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum StyleMode {
+    /// styles in a single style argument, arguments as they were set
+    #[default]
+    AsSet,
+    /// styles that are also presentation arguments are written as arguments,
+    /// as `fill="red"`, for tools that do not read css
+    Attributes,
+    /// presentation arguments are moved into the style argument
+    Styles,
+}
+
+/// The properties that can also be set as arguments
+const PRESENTATION: &[&str] = &[
+    "alignment-baseline",
+    "baseline-shift",
+    "clip",
+    "clip-path",
+    "clip-rule",
+    "color",
+    "color-interpolation",
+    "color-interpolation-filters",
+    "color-rendering",
+    "cursor",
+    "direction",
+    "display",
+    "dominant-baseline",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "filter",
+    "flood-color",
+    "flood-opacity",
+    "font-family",
+    "font-size",
+    "font-size-adjust",
+    "font-stretch",
+    "font-style",
+    "font-variant",
+    "font-weight",
+    "image-rendering",
+    "letter-spacing",
+    "lighting-color",
+    "marker-end",
+    "marker-mid",
+    "marker-start",
+    "mask",
+    "opacity",
+    "overflow",
+    "paint-order",
+    "pointer-events",
+    "shape-rendering",
+    "stop-color",
+    "stop-opacity",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "text-anchor",
+    "text-decoration",
+    "text-rendering",
+    "unicode-bidi",
+    "vector-effect",
+    "visibility",
+    "word-spacing",
+    "writing-mode",
+];
+
+/// true if k can be written as either an argument or a style
+pub fn is_presentation(k: &str) -> bool {
+    PRESENTATION.contains(&k)
+}
+
+/// Animation elements, where "fill" is the timing attribute, not the paint
+const ANIMATION: &[&str] = &["animate", "animateMotion", "animateTransform", "set"];
+
+/// as is_presentation, for an argument on the element "name"
+fn is_presentation_on(name: &str, k: &str) -> bool {
+    is_presentation(k) && !(k == "fill" && ANIMATION.contains(&name))
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
 pub struct Args {
     items: Vec<Arg>,
+    mode: Option<StyleMode>,
 }
 
 /// # example
@@ -48,52 +150,208 @@ pub struct Args {
 /// let a = Args::new().arg("f",7).style("p","rt");
 /// assert_eq!(r#"f="7" style="p:rt;" "#,&format!("{}",a));
 /// ```
+///
+/// each argument and style has one value, setting it again replaces it.
+///
+/// ```
+/// use mksvg::args::{Args,SvgArg};
+/// let mut a = Args::new().x(1).fill("red").y(2).x(3).fill("blue");
+/// assert_eq!(a.get_style("fill"),Some("blue"));
+/// a.remove_style("fill");
+/// assert_eq!(r#"x="3" y="2" "#,&format!("{}",a));
+/// ```
 impl Args {
     pub fn new() -> Args {
-        Args { items: Vec::new() }
+        Args {
+            items: Vec::new(),
+            mode: None,
+        }
+    }
+
+    /// get the value of an xml argument
+    ///
+    /// ```
+    /// use mksvg::args::{Args,SvgArg};
+    /// let a = Args::new().id("card").fill("red");
+    /// assert_eq!(a.get("id"),Some("card"));
+    /// assert_eq!(a.get("fill"),None);
+    /// ```
+    pub fn get(&self, k: &str) -> Option<&str> {
+        self.find(ARG, k)
+    }
+
+    /// get the value of a style property
+    pub fn get_style(&self, k: &str) -> Option<&str> {
+        self.find(STYLE, k)
+    }
+
+    fn find(&self, tp: AType, k: &str) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|a| a.tp == tp && a.k == k)
+            .map(|a| a.v.as_str())
+    }
+
+    /// Set an argument or style, replacing any earlier value for the key in place,
+    /// transforms are always added to the end.
+    fn set(mut self, a: Arg) -> Self {
+        let old = match a.tp {
+            TRANS => None,
+            _ => self.items.iter_mut().find(|b| b.tp == a.tp && b.k == a.k),
+        };
+        match old {
+            Some(b) => *b = a,
+            None => self.items.push(a),
+        }
+        self
+    }
+
+    /// add an xml argument that will be written without escaping,
+    /// only use this for values that are already valid inside quotes.
+    pub fn raw_arg<T: Display>(self, k: &str, v: T) -> Self {
+        self.set(Arg {
+            k: k.to_string(),
+            v: v.to_string(),
+            tp: ARG,
+            raw: true,
+        })
+    }
+
+    fn of_type(&self, tp: AType) -> impl Iterator<Item = (&str, &str)> {
+        self.items
+            .iter()
+            .filter(move |a| a.tp == tp)
+            .map(|a| (a.k.as_str(), a.v.as_str()))
+    }
+
+    /// the xml arguments as (key,value), not including style and transform
+    pub fn args(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.of_type(ARG)
+    }
+
+    /// the style items as (property,value)
+    pub fn styles(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.of_type(STYLE)
+    }
+
+    /// the transforms as (name,comma separated args) in the order they are applied
+    pub fn transforms(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.of_type(TRANS)
+    }
+
+    /// remove the xml argument with the given key
+    pub fn remove(&mut self, k: &str) {
+        self.items.retain(|a| a.tp != ARG || a.k != k);
+    }
+
+    /// remove the style property with the given key
+    pub fn remove_style(&mut self, k: &str) {
+        self.items.retain(|a| a.tp != STYLE || a.k != k);
+    }
+
+    pub fn clear_transforms(&mut self) {
+        self.items.retain(|a| a.tp != TRANS);
+    }
+
+    pub fn clear_styles(&mut self) {
+        self.items.retain(|a| a.tp != STYLE);
+    }
+
+    /// Combine with other, its arguments and styles replace any with the same key,
+    /// and its transforms are applied after these.
+    ///
+    /// ```
+    /// use mksvg::args::{Args,SvgArg};
+    /// let base = Args::new().stroke("black").fill("white").translate(5,5);
+    /// let card = Args::new().fill("red").x(3).rotate(90,0,0);
+    /// assert_eq!(
+    ///     base.merge(&card).to_string(),
+    ///     r#"x="3" style="stroke:black;fill:red;" transform="translate(5,5) rotate(90,0,0) " "#
+    /// );
+    /// ```
+    pub fn merge(mut self, other: &Args) -> Self {
+        self.mode = other.mode.or(self.mode);
+        other.items.iter().cloned().fold(self, Args::set)
+    }
+
+    /// Write these args with this mode, whatever the writer's Format says
+    pub fn style_mode(mut self, m: StyleMode) -> Self {
+        self.mode = Some(m);
+        self
     }
 }
 
-impl Display for Args {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let mut astr = "".to_string();
+impl Args {
+    /// each xml argument as it will be written, styles and transforms are collected
+    /// into a single argument each. If compact the transform has no trailing space.
+    /// mode is used unless these args have their own. When a property is set as
+    /// both, the style is kept, as it would win in the renderer.
+    /// name is the element the args are for, so fill on an animation stays an argument.
+    pub(crate) fn parts(&self, name: &str, compact: bool, mode: StyleMode) -> Vec<String> {
+        let mode = self.mode.unwrap_or(mode);
+        let mut res = Vec::new();
         let mut sstr = "".to_string();
         let mut tstr = "".to_string();
         for a in &self.items {
-            match a.tp {
-                ARG => astr.push_str(&format!(r#"{}="{}" "#, &a.k, a.v)),
-                STYLE => sstr.push_str(&format!("{}:{};", &a.k, a.v)),
-                TRANS => tstr.push_str(&format!("{}({}) ", &a.k, a.v)),
+            let pres = mode != StyleMode::AsSet && is_presentation_on(name, &a.k);
+            match (a.tp, pres) {
+                (ARG, true) if self.get_style(&a.k).is_some() => {}
+                (ARG, true) if mode == StyleMode::Styles => {
+                    sstr.push_str(&format!("{}:{};", &a.k, a.value()))
+                }
+                (STYLE, true) if mode == StyleMode::Attributes => {
+                    res.push(format!(r#"{}="{}""#, &a.k, a.value()))
+                }
+                (ARG, _) => res.push(format!(r#"{}="{}""#, &a.k, a.value())),
+                (STYLE, _) => sstr.push_str(&format!("{}:{};", &a.k, a.value())),
+                (TRANS, _) => tstr.push_str(&format!("{}({}) ", &a.k, a.value())),
             }
         }
-        if sstr.len() > 0 {
-            astr.push_str(&format!(r#"style="{}" "#, &sstr));
+        if !sstr.is_empty() {
+            res.push(format!(r#"style="{}""#, &sstr));
+        }
+        if compact {
+            tstr.pop();
         }
-        if tstr.len() > 0 {
-            astr.push_str(&format!(r#"transform="{}" "#, &tstr));
+        if !tstr.is_empty() {
+            res.push(format!(r#"transform="{}""#, &tstr));
         }
-        write!(f, "{}", astr)
+        res
     }
 }
 
+impl Display for Args {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for p in self.parts("", false, StyleMode::AsSet) {
+            write!(f, "{} ", p)?;
+        }
+        Ok(())
+    }
+}
+
+/// "style" and "transform" arguments are split into their parts, as when parsing,
+/// so they combine with the other styles and transforms instead of repeating.
 impl SvgArg for Args {
-    fn arg<T: Display>(mut self, k: &str, v: T) -> Self {
-        self.items.push(Arg {
+    fn arg<T: Display>(self, k: &str, v: T) -> Self {
+        if k == "style" || k == "transform" {
+            return self.xml_arg(k, &v.to_string());
+        }
+        self.set(Arg {
             k: k.to_string(),
             v: v.to_string(),
             tp: ARG,
-        });
-        self
+            raw: false,
+        })
     }
-    fn style<T: Display>(mut self, k: &str, v: T) -> Self {
-        self.items.push(Arg {
+    fn style<T: Display>(self, k: &str, v: T) -> Self {
+        self.set(Arg {
             k: k.to_string(),
             v: format!("{}", v),
             tp: STYLE,
-        });
-        self
+            raw: false,
+        })
     }
-    fn transform<T: Display>(mut self, k: &str, args: &[T]) -> Self {
+    fn transform<T: Display>(self, k: &str, args: &[T]) -> Self {
         let mut vstr = "".to_string();
         let mut first = true;
         for s in args {
@@ -104,12 +362,12 @@ impl SvgArg for Args {
             }
             vstr.push_str(&format!(",{}", s));
         }
-        self.items.push(Arg {
+        self.set(Arg {
             k: k.to_string(),
             v: vstr,
             tp: TRANS,
-        });
-        self
+            raw: false,
+        })
     }
 }
 
@@ -144,6 +402,22 @@ pub trait SvgArg: Sized + Display {
     fn font_weight<T: Display>(self, n: T) -> Self {
         self.style("font-weight", n)
     }
+    /// apply a filter, such as the IdRef from writing a filter::Filter
+    fn filter<T: Display>(self, f: T) -> Self {
+        self.style("filter", f)
+    }
+    /// place a marker::Marker on the first point
+    fn marker_start<T: Display>(self, m: T) -> Self {
+        self.style("marker-start", m)
+    }
+    /// place a marker::Marker on every point but the first and last
+    fn marker_mid<T: Display>(self, m: T) -> Self {
+        self.style("marker-mid", m)
+    }
+    /// place a marker::Marker on the last point
+    fn marker_end<T: Display>(self, m: T) -> Self {
+        self.style("marker-end", m)
+    }
 
     //args
 
@@ -172,6 +446,28 @@ pub trait SvgArg: Sized + Display {
     fn cx<T: Display>(self, n: T) -> Self {
         self.arg("cx", n)
     }
+    fn r<T: Display>(self, n: T) -> Self {
+        self.arg("r", n)
+    }
+    fn x1<T: Display>(self, n: T) -> Self {
+        self.arg("x1", n)
+    }
+    fn y1<T: Display>(self, n: T) -> Self {
+        self.arg("y1", n)
+    }
+    fn x2<T: Display>(self, n: T) -> Self {
+        self.arg("x2", n)
+    }
+    fn y2<T: Display>(self, n: T) -> Self {
+        self.arg("y2", n)
+    }
+    fn points<T: Display>(self, pts: &[(T, T)]) -> Self {
+        let v: Vec<String> = pts.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+        self.arg("points", v.join(" "))
+    }
+    fn view_box<T: Display>(self, x: T, y: T, w: T, h: T) -> Self {
+        self.arg("viewBox", format!("{} {} {} {}", x, y, w, h))
+    }
     fn rx<T: Display>(self, n: T) -> Self {
         self.arg("rx", n)
     }
@@ -216,7 +512,10 @@ pub trait SvgArg: Sized + Display {
         self.transform("skewY", &[y])
     }
     fn matrix<T: Display>(self, args: &[T]) -> Self {
-        self.transform("scale", args)
+        self.transform("matrix", args)
+    }
+    fn transform_by(self, t: &Transform) -> Self {
+        self.matrix(&t.to_array())
     }
     //shorners
 
@@ -229,4 +528,29 @@ pub trait SvgArg: Sized + Display {
     fn t_anc<T: Display>(self, n: T) -> Self {
         self.text_anchor(n)
     }
+
+    //accessibility
+
+    /// the ARIA role, such as "img", "group" or "list"
+    fn role<T: Display>(self, r: T) -> Self {
+        self.arg("role", r)
+    }
+    fn aria_label<T: Display>(self, l: T) -> Self {
+        self.arg("aria-label", l)
+    }
+    /// the ids of the elements that label this one, separated by spaces
+    fn aria_labelledby<T: Display>(self, ids: T) -> Self {
+        self.arg("aria-labelledby", ids)
+    }
+    fn aria_describedby<T: Display>(self, ids: T) -> Self {
+        self.arg("aria-describedby", ids)
+    }
+    /// hide decoration from screen readers
+    fn aria_hidden(self) -> Self {
+        self.arg("aria-hidden", "true")
+    }
+    /// the language of the content, such as "en" or "fr-CA"
+    fn lang<T: Display>(self, l: T) -> Self {
+        self.arg("lang", l)
+    }
 }
@@ -0,0 +1,193 @@
+//! Clipping and masking.
+//!
+//! clip_to and mask_with define the clipPath or mask, then return a group wrapping the
+//! writer, so everything written to it is clipped or masked. With a resource registry the
+//! definition goes into the page's defs, otherwise it is written in place first, and
+//! later clips on the same writer are numbered "clip_1", "clip_2" ...
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::clip::clip_to;
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     let frame = Tag::rect(0, 0, 50, 70).rx(5);
+//!     let mut art = clip_to(&mut svg, frame).unwrap();
+//!     Tag::img("art.png", 0, 0, 50, 70).write(&mut art).unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r#"<defs >
+//!   <clipPath id="clip" >
+//!     <rect x="0" y="0" width="50" height="70" rx="5" />
+//!   </clipPath>
+//! </defs>
+//! <g style="clip-path:url(#clip);" >
+//!   <image x="0" y="0" width="50" height="70" href="art.png" />
+//! </g>
+//! "#
+//! );
+//! ```
+
+use crate::args::SvgArg;
+use crate::gradient::Units;
+use crate::resource::{define, Resource};
+use crate::tag::Tag;
+use crate::write::{SvgWrite, TransWrap};
+use std::fmt::Display;
+
+/// A clipPath, anything outside its shapes is not drawn
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClipPath {
+    id: String,
+    tag: Tag,
+}
+
+impl ClipPath {
+    pub fn new<T: Display>(id: T) -> Self {
+        ClipPath {
+            id: id.to_string(),
+            tag: Tag::clip_path(),
+        }
+    }
+
+    /// add a shape, a Tag or a PathD
+    pub fn shape<S: Into<Tag>>(mut self, s: S) -> Self {
+        self.tag = self.tag.child(s.into());
+        self
+    }
+
+    /// ObjectBoundingBox makes the shapes relative to the size of what is clipped
+    pub fn units(mut self, u: Units) -> Self {
+        self.tag = self.tag.arg("clipPathUnits", u);
+        self
+    }
+
+    /// Define the clip path, and wrap w in a group clipped by it
+    pub fn wrap<'a, W: SvgWrite<Err = E>, E>(&self, w: &'a mut W) -> Result<TransWrap<'a, E>, E> {
+        let id = define(w, self)?;
+        Ok(Tag::g().style("clip-path", id).wrap(w))
+    }
+}
+
+impl Resource for ClipPath {
+    fn id_prefix(&self) -> &str {
+        &self.id
+    }
+    fn write_def<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+        self.tag.clone().id(id).write(w)
+    }
+}
+
+/// A mask, the luminance of its content sets how visible the masked content is,
+/// white shows it, black hides it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mask {
+    id: String,
+    tag: Tag,
+}
+
+impl Mask {
+    pub fn new<T: Display>(id: T) -> Self {
+        Mask {
+            id: id.to_string(),
+            tag: Tag::new("mask"),
+        }
+    }
+
+    /// add content to the mask
+    pub fn child<S: Into<Tag>>(mut self, s: S) -> Self {
+        self.tag = self.tag.child(s.into());
+        self
+    }
+
+    /// ObjectBoundingBox makes the content relative to the size of what is masked
+    pub fn units(mut self, u: Units) -> Self {
+        self.tag = self.tag.arg("maskContentUnits", u);
+        self
+    }
+
+    /// Define the mask, and wrap w in a group masked by it
+    pub fn wrap<'a, W: SvgWrite<Err = E>, E>(&self, w: &'a mut W) -> Result<TransWrap<'a, E>, E> {
+        let id = define(w, self)?;
+        Ok(Tag::g().style("mask", id).wrap(w))
+    }
+}
+
+impl Resource for Mask {
+    fn id_prefix(&self) -> &str {
+        &self.id
+    }
+    fn write_def<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+        self.tag.clone().id(id).write(w)
+    }
+}
+
+/// Wrap w in a group clipped to shape, a Tag or a PathD
+pub fn clip_to<'a, W: SvgWrite<Err = E>, E, S: Into<Tag>>(
+    w: &'a mut W,
+    shape: S,
+) -> Result<TransWrap<'a, E>, E> {
+    ClipPath::new("clip").shape(shape).wrap(w)
+}
+
+/// Wrap w in a group masked by content, a Tag or a PathD
+pub fn mask_with<'a, W: SvgWrite<Err = E>, E, S: Into<Tag>>(
+    w: &'a mut W,
+    content: S,
+) -> Result<TransWrap<'a, E>, E> {
+    Mask::new("mask").child(content).wrap(w)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::path::PathD;
+    use crate::resource::ResourceWrap;
+    use crate::validate::Validator;
+    use crate::write::SvgFmt;
+
+    #[test]
+    pub fn test_two_clips_without_registry() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 50, 50).unwrap();
+            let mut a = clip_to(&mut svg, Tag::rect(0, 0, 10, 10)).unwrap();
+            Tag::circle(5, 5, 5).write(&mut a).unwrap();
+            drop(a);
+            let mut b = clip_to(&mut svg, Tag::circle(0, 0, 3)).unwrap();
+            Tag::circle(5, 5, 5).write(&mut b).unwrap();
+        }
+        assert!(v.report().is_ok());
+        assert!(s.contains(r#"<clipPath id="clip" >"#));
+        assert!(s.contains(r#"<clipPath id="clip_1" >"#));
+        assert!(s.contains(r#"<g style="clip-path:url(#clip);" >"#));
+        assert!(s.contains(r#"<g style="clip-path:url(#clip_1);" >"#));
+    }
+
+    #[test]
+    pub fn test_clip_and_mask_on_page() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 50, 50).unwrap();
+            let mut page = ResourceWrap::new(&mut svg);
+            for _ in 0..2 {
+                let p = PathD::abs().m(0, 0).l(10, 0).l(5, 10).z();
+                let mut c = clip_to(&mut page, p).unwrap();
+                let fade = Tag::rect(0, 0, 10, 10).fill("white").style("opacity", 0.5);
+                let mut m = mask_with(&mut c, fade).unwrap();
+                Tag::circle(5, 5, 5).write(&mut m).unwrap();
+            }
+        }
+        assert!(v.report().is_ok());
+        assert_eq!(s.matches("<clipPath").count(), 1);
+        assert_eq!(s.matches("<mask").count(), 1);
+        assert_eq!(
+            s.matches(r#"<g style="clip-path:url(#clip);" >"#).count(),
+            2
+        );
+        assert_eq!(s.matches(r#"<g style="mask:url(#mask);" >"#).count(), 2);
+    }
+}
@@ -0,0 +1,489 @@
+//! A typed color, so palettes can be worked out from a base color instead of written by hand.
+//!
+//! Colors parse from names, hex and the css rgb() and hsl() forms, and display as hex,
+//! or rgba() if they are not opaque, so they can be passed to fill, stroke and any other
+//! style method.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::color::Color;
+//! let base: Color = "#3366cc".parse().unwrap();
+//! let light = base.lighten(0.2);
+//! assert_eq!(light.to_string(), "#85a3e0");
+//! assert_eq!(base.tint(0.5).to_string(), "#99b3e6");
+//! assert_eq!(base.alpha(0.5).to_string(), "rgba(51,102,204,0.5)");
+//! assert_eq!(
+//!     Tag::rect(0, 0, 5, 5).fill(base).stroke(base.darken(0.2)).to_string(),
+//!     r#"<rect x="0" y="0" width="5" height="5" style="fill:#3366cc;stroke:#1f3d7a;" />"#
+//! );
+//! ```
+
+use crate::err::ColorError;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// An sRGB color, with alpha from 0 to 1
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f64,
+}
+
+fn clamp01(v: f64) -> f64 {
+    v.clamp(0., 1.)
+}
+
+fn to_u8(v: f64) -> u8 {
+    (clamp01(v) * 255.).round() as u8
+}
+
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 1. }
+    }
+
+    pub fn rgba(r: u8, g: u8, b: u8, a: f64) -> Self {
+        Color {
+            r,
+            g,
+            b,
+            a: clamp01(a),
+        }
+    }
+
+    /// hue in degrees, saturation and lightness from 0 to 1
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        let (s, l) = (clamp01(s), clamp01(l));
+        let c = (1. - (2. * l - 1.).abs()) * s;
+        Color::from_chroma(h, c, l - c / 2.)
+    }
+
+    /// hue in degrees, saturation and value from 0 to 1
+    pub fn from_hsv(h: f64, s: f64, v: f64) -> Self {
+        let (s, v) = (clamp01(s), clamp01(v));
+        let c = v * s;
+        Color::from_chroma(h, c, v - c)
+    }
+
+    fn from_chroma(h: f64, c: f64, m: f64) -> Self {
+        let h = h.rem_euclid(360.) / 60.;
+        let x = c * (1. - (h % 2. - 1.).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (c, x, 0.),
+            1 => (x, c, 0.),
+            2 => (0., c, x),
+            3 => (0., x, c),
+            4 => (x, 0., c),
+            _ => (c, 0., x),
+        };
+        Color::rgb(to_u8(r + m), to_u8(g + m), to_u8(b + m))
+    }
+
+    /// The channels from 0 to 1, with the max, min and hue in degrees
+    fn hue(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (
+            self.r as f64 / 255.,
+            self.g as f64 / 255.,
+            self.b as f64 / 255.,
+        );
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+        let h = if d == 0. {
+            0.
+        } else if max == r {
+            60. * ((g - b) / d).rem_euclid(6.)
+        } else if max == g {
+            60. * ((b - r) / d + 2.)
+        } else {
+            60. * ((r - g) / d + 4.)
+        };
+        (max, min, h)
+    }
+
+    /// (hue in degrees, saturation, lightness)
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (max, min, h) = self.hue();
+        let l = (max + min) / 2.;
+        let s = if max == min {
+            0.
+        } else {
+            (max - min) / (1. - (2. * l - 1.).abs())
+        };
+        (h, s, l)
+    }
+
+    /// (hue in degrees, saturation, value)
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let (max, min, h) = self.hue();
+        let s = if max == 0. { 0. } else { (max - min) / max };
+        (h, s, max)
+    }
+
+    /// Look up a css color name, such as "gold" or "transparent"
+    pub fn named(s: &str) -> Option<Self> {
+        let s = s.to_ascii_lowercase();
+        if s == "transparent" {
+            return Some(Color::rgba(0, 0, 0, 0.));
+        }
+        NAMED
+            .iter()
+            .find(|(n, _)| *n == s)
+            .map(|(_, v)| Color::rgb((v >> 16) as u8, (v >> 8) as u8, *v as u8))
+    }
+
+    fn with_hsl(&self, h: f64, s: f64, l: f64) -> Self {
+        Color::from_hsl(h, s, l).alpha(self.a)
+    }
+
+    /// Raise the lightness by amount, from 0 to 1
+    pub fn lighten(&self, amount: f64) -> Self {
+        let (h, s, l) = self.to_hsl();
+        self.with_hsl(h, s, l + amount)
+    }
+
+    pub fn darken(&self, amount: f64) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Raise the saturation by amount, from 0 to 1
+    pub fn saturate(&self, amount: f64) -> Self {
+        let (h, s, l) = self.to_hsl();
+        self.with_hsl(h, s + amount, l)
+    }
+
+    /// Turn the hue by degrees
+    pub fn spin(&self, degrees: f64) -> Self {
+        let (h, s, l) = self.to_hsl();
+        self.with_hsl(h + degrees, s, l)
+    }
+
+    /// t of the way from this color to other, alpha included
+    pub fn mix(&self, other: Color, t: f64) -> Self {
+        let t = clamp01(t);
+        let f = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color {
+            r: f(self.r, other.r),
+            g: f(self.g, other.g),
+            b: f(self.b, other.b),
+            a: self.a + (other.a - self.a) * t,
+        }
+    }
+
+    /// Mix with white
+    pub fn tint(&self, t: f64) -> Self {
+        self.mix(Color::WHITE.alpha(self.a), t)
+    }
+
+    /// Mix with black
+    pub fn shade(&self, t: f64) -> Self {
+        self.mix(Color::BLACK.alpha(self.a), t)
+    }
+
+    /// The same color with a new alpha
+    pub fn alpha(&self, a: f64) -> Self {
+        Color::rgba(self.r, self.g, self.b, a)
+    }
+}
+
+/// Hex when opaque, otherwise rgba()
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.a >= 1. {
+            write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            let a = (self.a * 1000.).round() / 1000.;
+            write!(f, "rgba({},{},{},{})", self.r, self.g, self.b, a)
+        }
+    }
+}
+
+fn parse_hex(s: &str) -> Option<Color> {
+    if !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let v = u32::from_str_radix(s, 16).ok()?;
+    let short = |n: u32| (n & 0xF) as u8 * 17;
+    match s.len() {
+        3 => Some(Color::rgb(short(v >> 8), short(v >> 4), short(v))),
+        4 => Some(Color::rgba(
+            short(v >> 12),
+            short(v >> 8),
+            short(v >> 4),
+            short(v) as f64 / 255.,
+        )),
+        6 => Some(Color::rgb((v >> 16) as u8, (v >> 8) as u8, v as u8)),
+        8 => Some(Color::rgba(
+            (v >> 24) as u8,
+            (v >> 16) as u8,
+            (v >> 8) as u8,
+            (v & 0xFF) as f64 / 255.,
+        )),
+        _ => None,
+    }
+}
+
+/// A number, or a percentage of "full"
+fn parse_num(s: &str, full: f64) -> Option<f64> {
+    match s.strip_suffix('%') {
+        Some(p) => p.trim().parse::<f64>().ok().map(|v| v * full / 100.),
+        None => s.trim_end_matches("deg").parse().ok(),
+    }
+}
+
+/// The arguments of rgb() or hsl(), comma or space separated, with an optional alpha
+fn parse_fn(s: &str, name: &str) -> Option<Vec<String>> {
+    let s = s.strip_prefix(name)?;
+    let s = s.strip_prefix('a').unwrap_or(s).trim_start();
+    let s = s.strip_prefix('(')?.strip_suffix(')')?;
+    let v: Vec<String> = s
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|p| !p.is_empty())
+        .map(|p| p.to_string())
+        .collect();
+    match v.len() {
+        3 | 4 => Some(v),
+        _ => None,
+    }
+}
+
+fn parse_alpha(v: &[String]) -> Option<f64> {
+    match v.get(3) {
+        Some(a) => parse_num(a, 1.),
+        None => Some(1.),
+    }
+}
+
+impl FromStr for Color {
+    type Err = ColorError;
+    fn from_str(s: &str) -> Result<Self, ColorError> {
+        let l = s.trim().to_ascii_lowercase();
+        let res = if let Some(h) = l.strip_prefix('#') {
+            parse_hex(h)
+        } else if let Some(v) = parse_fn(&l, "rgb") {
+            let c = |i: usize| parse_num(&v[i], 255.).map(|n| to_u8(n / 255.));
+            parse_alpha(&v).and_then(|a| Some(Color::rgba(c(0)?, c(1)?, c(2)?, a)))
+        } else if let Some(v) = parse_fn(&l, "hsl") {
+            let c = |i: usize| parse_num(&v[i], 1.);
+            parse_alpha(&v).and_then(|a| Some(Color::from_hsl(c(0)?, c(1)?, c(2)?).alpha(a)))
+        } else {
+            Color::named(&l)
+        };
+        res.ok_or_else(|| ColorError(s.to_string()))
+    }
+}
+
+/// The css named colors
+const NAMED: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn c(s: &str) -> Color {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    pub fn test_parse_forms() {
+        let red = Color::rgb(255, 0, 0);
+        assert_eq!(c("red"), red);
+        assert_eq!(c(" Red "), red);
+        assert_eq!(c("#f00"), red);
+        assert_eq!(c("#FF0000"), red);
+        assert_eq!(c("rgb(255, 0, 0)"), red);
+        assert_eq!(c("rgb(100% 0% 0%)"), red);
+        assert_eq!(c("hsl(0, 100%, 50%)"), red);
+        assert_eq!(c("hsl(360deg 100% 50%)"), red);
+        assert_eq!(c("#ff000080"), Color::rgba(255, 0, 0, 128. / 255.));
+        assert_eq!(c("#f008").a, 136. / 255.);
+        assert_eq!(c("rgba(255,0,0,0.5)"), red.alpha(0.5));
+        assert_eq!(c("rgb(255 0 0 / 50%)"), red.alpha(0.5));
+        assert_eq!(c("transparent").a, 0.);
+        assert_eq!(c("rebeccapurple"), Color::rgb(0x66, 0x33, 0x99));
+        assert!("#ff00".parse::<Color>().is_ok());
+        assert!("#ff0g00".parse::<Color>().is_err());
+        assert!("#+ff".parse::<Color>().is_err());
+        assert!("#+fffff".parse::<Color>().is_err());
+        assert!("rgb(1,2)".parse::<Color>().is_err());
+        assert!("nocolor".parse::<Color>().is_err());
+    }
+
+    #[test]
+    pub fn test_conversions_and_blends() {
+        let base = Color::rgb(51, 102, 204);
+        let (h, s, l) = base.to_hsl();
+        assert_eq!(Color::from_hsl(h, s, l), base);
+        let (h, s, v) = base.to_hsv();
+        assert_eq!(
+            (h.round(), (s * 100.).round(), (v * 100.).round()),
+            (220., 75., 80.)
+        );
+        assert_eq!(Color::from_hsv(h, s, v), base);
+        assert_eq!(base.spin(360.), base);
+        assert_eq!(base.lighten(1.), Color::WHITE);
+        assert_eq!(base.darken(1.), Color::BLACK);
+        assert_eq!(base.saturate(-1.).to_hsl().1, 0.);
+        assert_eq!(
+            Color::BLACK.mix(Color::WHITE, 0.5),
+            Color::rgb(128, 128, 128)
+        );
+        assert_eq!(base.alpha(0.5).shade(1.), Color::BLACK.alpha(0.5));
+        assert_eq!(Color::BLACK.alpha(2. / 3.).to_string(), "rgba(0,0,0,0.667)");
+    }
+}
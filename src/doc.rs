@@ -0,0 +1,503 @@
+//! SvgDoc is an SvgWrite that builds a tree of nodes instead of streaming text.
+//!
+//! Anything written to it (Tags, Text, TransWrap groups, raw strings) is read back into
+//! Elements, so after the writers have finished the document can be searched, modified
+//! and then written out again through any other SvgWrite.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::doc::SvgDoc;
+//! let mut doc = SvgDoc::new();
+//! {
+//!     let mut g = Tag::g().id("art").wrap(&mut doc);
+//!     Tag::rect(0, 0, 5, 5).class("debug").write(&mut g).unwrap();
+//!     Tag::rect(1, 1, 3, 3).write(&mut g).unwrap();
+//! }
+//! doc.retain(|e| !e.has_class("debug"));
+//! doc.find_by_id_mut("art").unwrap().set("id", "card");
+//!
+//! let mut s = String::new();
+//! doc.write_to(&mut SvgFmt::new(&mut s)).unwrap();
+//! assert_eq!(s, "<g id=\"card\" >\n  <rect x=\"1\" y=\"1\" width=\"3\" height=\"3\" />\n</g>\n");
+//! ```
+//!
+//! Existing files can be read into an SvgDoc using the "parse" mod.
+
+use crate::args::{Args, SvgArg};
+use crate::err::DocError;
+use crate::escape::escape_text;
+use crate::format::Format;
+use crate::parse::{tokens, Token};
+use crate::resource::Ids;
+use crate::write::{SvgFmt, SvgWrite};
+use std::fmt;
+use std::fmt::Display;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Elem(Element),
+    Text(String),
+    /// Markup that is kept verbatim, such as the xml declaration and comments
+    Raw(String),
+}
+
+impl Node {
+    pub fn write<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
+        match self {
+            Node::Elem(e) => e.write(w),
+            Node::Text(s) => w.write_text(s),
+            Node::Raw(s) => w.write(s),
+        }
+    }
+}
+
+impl From<Element> for Node {
+    fn from(e: Element) -> Self {
+        Node::Elem(e)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Element {
+    pub name: String,
+    pub args: Args,
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Element {
+            name: name.into(),
+            args: Args::new(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, k: &str) -> Option<&str> {
+        self.args.get(k)
+    }
+
+    /// replace any existing value of k with v, keeping its place.
+    /// Setting "style" or "transform" replaces all the styles or transforms.
+    pub fn set<T: Display>(&mut self, k: &str, v: T) {
+        match k {
+            "style" => self.args.clear_styles(),
+            "transform" => self.args.clear_transforms(),
+            _ => {}
+        }
+        self.args = std::mem::take(&mut self.args).arg(k, v);
+    }
+
+    pub fn remove(&mut self, k: &str) {
+        self.args.remove(k);
+    }
+
+    pub fn has_class(&self, c: &str) -> bool {
+        match self.get("class") {
+            Some(cl) => cl.split_whitespace().any(|v| v == c),
+            None => false,
+        }
+    }
+
+    pub fn push<N: Into<Node>>(&mut self, n: N) {
+        self.children.push(n.into());
+    }
+
+    /// Writes the element and its children, an element containing text, or a
+    /// text element, is written on a single line, as Text does.
+    pub fn write<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
+        write_tree(&self.name, &self.args, &self.children, w)
+    }
+}
+
+/// Elements where whitespace between children is part of the text
+const TEXT_CONTENT: &[&str] = &["text", "tspan", "textPath"];
+
+/// The element on one line, so no whitespace is added around its text
+fn inline(name: &str, args: &Args, children: &[Node], f: &Format, d: i8) -> String {
+    if children.is_empty() {
+        return f.tag(d, name, args, "/>");
+    }
+    let mut s = f.tag(d, name, args, ">");
+    for c in children {
+        match c {
+            Node::Elem(e) => s.push_str(&inline(&e.name, &e.args, &e.children, f, d)),
+            Node::Text(t) => s.push_str(&escape_text(t)),
+            Node::Raw(r) => s.push_str(r),
+        }
+    }
+    s.push_str(&format!("</{}>", name));
+    s
+}
+
+/// Write an element from its parts, shared by Element and Tag.
+/// Elements containing text or CDATA, and text elements, are written on a single line.
+pub(crate) fn write_tree<W: SvgWrite<Err = E>, E>(
+    name: &str,
+    args: &Args,
+    children: &[Node],
+    w: &mut W,
+) -> Result<(), E> {
+    let f = w.format();
+    if children.is_empty()
+        || TEXT_CONTENT.contains(&name)
+        || children.iter().any(|c| match c {
+            Node::Text(_) => true,
+            Node::Raw(r) => r.starts_with("<![CDATA["),
+            _ => false,
+        })
+    {
+        return w.write(&inline(name, args, children, &f, w.depth()));
+    }
+    w.write(&f.tag(w.depth(), name, args, ">"))?;
+    w.inc_depth(1);
+    for c in children {
+        c.write(w)?;
+    }
+    w.inc_depth(-1);
+    w.write(&format!("</{}>", name))
+}
+
+impl SvgArg for Element {
+    fn arg<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.arg(k, v);
+        self
+    }
+    fn style<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.style(k, v);
+        self
+    }
+    fn transform<T: Display>(mut self, k: &str, args: &[T]) -> Self {
+        self.args = self.args.transform(k, args);
+        self
+    }
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(&mut SvgFmt::new(f))
+    }
+}
+
+fn find_id<'a>(nodes: &'a [Node], id: &str) -> Option<&'a Element> {
+    for n in nodes {
+        if let Node::Elem(e) = n {
+            if e.get("id") == Some(id) {
+                return Some(e);
+            }
+            if let Some(r) = find_id(&e.children, id) {
+                return Some(r);
+            }
+        }
+    }
+    None
+}
+
+fn find_id_mut<'a>(nodes: &'a mut [Node], id: &str) -> Option<&'a mut Element> {
+    for n in nodes {
+        if let Node::Elem(e) = n {
+            if e.get("id") == Some(id) {
+                return Some(e);
+            }
+            if let Some(r) = find_id_mut(&mut e.children, id) {
+                return Some(r);
+            }
+        }
+    }
+    None
+}
+
+fn find_all<'a, F: Fn(&Element) -> bool>(nodes: &'a [Node], f: &F, res: &mut Vec<&'a Element>) {
+    for n in nodes {
+        if let Node::Elem(e) = n {
+            if f(e) {
+                res.push(e);
+            }
+            find_all(&e.children, f, res);
+        }
+    }
+}
+
+fn visit_mut<F: FnMut(&mut Element)>(nodes: &mut [Node], f: &mut F) {
+    for n in nodes {
+        if let Node::Elem(e) = n {
+            f(e);
+            visit_mut(&mut e.children, f);
+        }
+    }
+}
+
+fn retain<F: FnMut(&Element) -> bool>(nodes: &mut Vec<Node>, f: &mut F) {
+    nodes.retain(|n| match n {
+        Node::Elem(e) => f(e),
+        _ => true,
+    });
+    for n in nodes {
+        if let Node::Elem(e) = n {
+            retain(&mut e.children, f);
+        }
+    }
+}
+
+/// An in memory document, see the module docs.
+///
+/// Elements are only added to the tree once they are closed, so the tree should be
+/// inspected after all wrapping writers have been dropped.
+#[derive(Debug, Default)]
+pub struct SvgDoc {
+    nodes: Vec<Node>,
+    open: Vec<Element>,
+    deferred: Option<DocError>,
+    ids: Ids,
+}
+
+impl SvgDoc {
+    pub fn new() -> Self {
+        SvgDoc::default()
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn nodes_mut(&mut self) -> &mut Vec<Node> {
+        &mut self.nodes
+    }
+
+    /// The first top level element, normally the "svg" tag
+    pub fn root(&self) -> Option<&Element> {
+        self.nodes.iter().find_map(|n| match n {
+            Node::Elem(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    pub fn root_mut(&mut self) -> Option<&mut Element> {
+        self.nodes.iter_mut().find_map(|n| match n {
+            Node::Elem(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// true if every opened tag has been closed
+    pub fn is_closed(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    pub fn find_by_id(&self, id: &str) -> Option<&Element> {
+        find_id(&self.nodes, id)
+    }
+
+    pub fn find_by_id_mut(&mut self, id: &str) -> Option<&mut Element> {
+        find_id_mut(&mut self.nodes, id)
+    }
+
+    pub fn find_by_class(&self, c: &str) -> Vec<&Element> {
+        self.find_all(|e| e.has_class(c))
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Vec<&Element> {
+        self.find_all(|e| e.name == name)
+    }
+
+    pub fn find_all<F: Fn(&Element) -> bool>(&self, f: F) -> Vec<&Element> {
+        let mut res = Vec::new();
+        find_all(&self.nodes, &f, &mut res);
+        res
+    }
+
+    /// call f on every element in the document, parents before children
+    pub fn for_each_mut<F: FnMut(&mut Element)>(&mut self, mut f: F) {
+        visit_mut(&mut self.nodes, &mut f);
+    }
+
+    /// remove every element (and its children) for which f returns false
+    pub fn retain<F: FnMut(&Element) -> bool>(&mut self, mut f: F) {
+        retain(&mut self.nodes, &mut f);
+    }
+
+    pub fn write_to<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
+        for n in &self.nodes {
+            n.write(w)?;
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, n: Node) {
+        match self.open.last_mut() {
+            Some(p) => p.children.push(n),
+            None => self.nodes.push(n),
+        }
+    }
+
+    pub(crate) fn add_token(&mut self, t: Token) -> Result<(), DocError> {
+        match t {
+            Token::Open(e) => self.open.push(e),
+            Token::Empty(e) => self.add(Node::Elem(e)),
+            Token::Close(name) => self.close(&name)?,
+            Token::Text(s) => {
+                // whitespace between elements is layout, unless inside text
+                let in_text = match self.open.last() {
+                    Some(p) => TEXT_CONTENT.contains(&p.name.as_str()),
+                    None => false,
+                };
+                if in_text || !s.trim().is_empty() {
+                    self.add(Node::Text(s))
+                }
+            }
+            Token::Raw(s) => self.add(Node::Raw(s)),
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, name: &str) -> Result<(), DocError> {
+        match self.open.pop() {
+            Some(e) if e.name == name => {
+                self.add(Node::Elem(e));
+                Ok(())
+            }
+            Some(e) => Err(DocError::Close {
+                expected: e.name,
+                got: name.to_string(),
+            }),
+            None => Err(DocError::UnexpectedClose(name.to_string())),
+        }
+    }
+}
+
+impl SvgWrite for SvgDoc {
+    type Err = DocError;
+    fn write(&mut self, s: &str) -> Result<(), DocError> {
+        if let Some(e) = self.deferred.take() {
+            return Err(e);
+        }
+        for (_, t) in tokens(s)? {
+            self.add_token(t)?;
+        }
+        Ok(())
+    }
+    /// Depth is ignored, structure comes from the tags themselves
+    fn inc_depth(&mut self, _n: i8) {}
+    fn ids(&mut self) -> Option<&mut Ids> {
+        Some(&mut self.ids)
+    }
+    fn defer_err(&mut self, e: DocError) {
+        self.deferred.get_or_insert(e);
+    }
+    fn flush(&mut self) -> Result<(), DocError> {
+        match self.deferred.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Display for SvgDoc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_to(&mut SvgFmt::new(f))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tag::Tag;
+    use crate::text::Text;
+    use crate::write::TransWrap;
+
+    #[test]
+    pub fn test_doc_builds_tree() {
+        let mut doc = SvgDoc::new();
+        {
+            let mut svg = Tag::start(&mut doc, 20, 20).unwrap();
+            let mut g = Tag::g().id("g1").wrap(&mut svg);
+            Tag::rect(0, 0, 5, 5).class("a b").write(&mut g).unwrap();
+            Text::new("hello", 0, 0, 5).write(&mut g).unwrap();
+        }
+        assert!(doc.is_closed());
+        let root = doc.root().unwrap();
+        assert_eq!(root.name, "svg");
+        assert_eq!(root.get("width"), Some("20"));
+        let g = doc.find_by_id("g1").unwrap();
+        assert_eq!(g.children.len(), 2);
+        assert_eq!(doc.find_by_class("b").len(), 1);
+        assert_eq!(
+            doc.find_by_name("text")[0].children,
+            vec![Node::Text("hello".to_string())]
+        );
+    }
+
+    #[test]
+    pub fn test_doc_rewrites_same_output() {
+        let mut direct = String::new();
+        let mut doc = SvgDoc::new();
+        fn draw<W: SvgWrite<Err = E>, E>(w: &mut W) -> Result<(), E> {
+            let mut svg = Tag::start(w, 20, 20)?;
+            let mut g = Tag::g().translate(3, 4).wrap(&mut svg);
+            Tag::rect(0, 0, 5, 5).fill("red").write(&mut g)?;
+            Text::new("hi\nthere", 0, 0, 5).write(&mut g)
+        }
+        draw(&mut SvgFmt::new(&mut direct)).unwrap();
+        draw(&mut doc).unwrap();
+        assert_eq!(doc.to_string(), direct);
+    }
+
+    #[test]
+    pub fn test_doc_mutation() {
+        let mut doc = SvgDoc::new();
+        {
+            let mut g = Tag::g().wrap(&mut doc);
+            Tag::rect(0, 0, 5, 5).id("r").write(&mut g).unwrap();
+            Tag::rect(0, 0, 5, 5).class("debug").write(&mut g).unwrap();
+        }
+        doc.retain(|e| !e.has_class("debug"));
+        doc.find_by_id_mut("r").unwrap().set("x", 2);
+        doc.root_mut().unwrap().push(Element::new("circle").cx(1));
+        assert_eq!(
+            doc.to_string(),
+            "<g >\n  <rect x=\"2\" y=\"0\" width=\"5\" height=\"5\" id=\"r\" />\n  <circle cx=\"1\" />\n</g>\n"
+        );
+    }
+
+    #[test]
+    pub fn test_set_replaces_style_and_transform() {
+        let mut e = Element::new("rect")
+            .fill("red")
+            .stroke("black")
+            .translate(1, 1);
+        e.set("style", "fill:blue");
+        e.set("transform", "rotate(3)");
+        assert_eq!(
+            e.to_string(),
+            "<rect style=\"fill:blue;\" transform=\"rotate(3) \" />\n"
+        );
+    }
+
+    #[test]
+    pub fn test_doc_close_errors() {
+        let mut doc = SvgDoc::new();
+        doc.write("<g>").unwrap();
+        match doc.write("</a>") {
+            Err(DocError::Close { expected, got }) => {
+                assert_eq!((&expected[..], &got[..]), ("g", "a"))
+            }
+            r => panic!("expected close error, got {:?}", r),
+        }
+        let mut doc = SvgDoc::new();
+        let mut w = TransWrap::new(&mut doc, "<g>", "</g>");
+        assert!(w.write("<rect x=3/>").is_err());
+        assert_eq!(
+            SvgDoc::new().write("</a>"),
+            Err(DocError::UnexpectedClose("a".to_string()))
+        );
+        let io = |k| DocError::IO(std::io::Error::new(k, "x"));
+        assert_eq!(
+            io(std::io::ErrorKind::NotFound),
+            io(std::io::ErrorKind::NotFound)
+        );
+        assert_ne!(
+            io(std::io::ErrorKind::NotFound),
+            io(std::io::ErrorKind::Other)
+        );
+    }
+}
@@ -9,6 +9,8 @@ pub enum PageError {
     FmtError(std::fmt::Error),
     #[error("Message{}", 0)]
     SMess(&'static str),
+    #[error("IO Error:{0}")]
+    IO(#[from] std::io::Error),
 }
 
 impl From<std::fmt::Error> for PageError {
@@ -16,3 +18,82 @@ impl From<std::fmt::Error> for PageError {
         PageError::FmtError(e)
     }
 }
+
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("Parse Error at {line}:{col}:{msg}")]
+pub struct ParseError {
+    pub line: usize,
+    pub col: usize,
+    pub msg: String,
+}
+
+#[derive(Error, Debug)]
+pub enum DocError {
+    #[error("{0}")]
+    Parse(#[from] ParseError),
+    #[error("IO Error:{0}")]
+    IO(#[from] std::io::Error),
+    #[error("Close Tag Error: expected </{expected}> got </{got}>")]
+    Close { expected: String, got: String },
+    #[error("Close Tag Error: </{0}> has no open tag")]
+    UnexpectedClose(String),
+}
+
+/// io::Error has no PartialEq, so IO errors are equal if they are the same kind
+impl PartialEq for DocError {
+    fn eq(&self, other: &Self) -> bool {
+        use DocError::*;
+        match (self, other) {
+            (Parse(a), Parse(b)) => a == b,
+            (IO(a), IO(b)) => a.kind() == b.kind(),
+            (
+                Close { expected, got },
+                Close {
+                    expected: e2,
+                    got: g2,
+                },
+            ) => expected == e2 && got == g2,
+            (UnexpectedClose(a), UnexpectedClose(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// A problem found by validate::Validator
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum Invalid {
+    #[error("{0}")]
+    Parse(ParseError),
+    #[error("Expected </{expected}> got </{got}>")]
+    Unbalanced { expected: String, got: String },
+    #[error("</{0}> has no open tag")]
+    UnexpectedClose(String),
+    #[error("Tags not closed: {0:?}")]
+    Unclosed(Vec<String>),
+    #[error("Unknown element <{0}>")]
+    UnknownElement(String),
+    #[error("Argument '{arg}' is not valid on <{element}>")]
+    BadArg { element: String, arg: String },
+    #[error("More than one root element, second is <{0}>")]
+    MultipleRoots(String),
+    #[error("Tag::start used below the root of the document")]
+    StartNotAtRoot,
+}
+
+#[derive(Error, Debug)]
+pub enum ValidError<E> {
+    #[error("{0}")]
+    Write(E),
+    #[error("Invalid Svg:{0}")]
+    Invalid(Invalid),
+}
+
+/// A string that could not be parsed as a color::Color
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("Invalid color: '{0}'")]
+pub struct ColorError(pub String);
+
+/// A string that could not be parsed as a transform::Transform
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("Invalid transform: {0}")]
+pub struct TransformError(pub String);
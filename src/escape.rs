@@ -0,0 +1,162 @@
+//! Xml escaping for argument values and text content.
+//!
+//! Args, Tag and Text escape everything they are given, and SvgWrite::write_text
+//! escapes raw text. SvgWrite::write itself is treated as trusted markup, as are
+//! values given with Args::raw_arg or Text::raw.
+//!
+//! ```
+//! use mksvg::escape::{escape_attr, escape_text, unescape};
+//! assert_eq!(escape_text("Draw 2 & <discard>"), "Draw 2 &amp; &lt;discard&gt;");
+//! assert_eq!(escape_attr("say \"hi\""), "say &quot;hi&quot;");
+//! assert_eq!(unescape("a &amp; &#98; &#x63;"), "a & b c");
+//! ```
+
+use std::borrow::Cow;
+
+fn escape_with(s: &str, quote: bool) -> Cow<'_, str> {
+    let needs = |c: char| c == '&' || c == '<' || c == '>' || (quote && c == '"');
+    if !s.contains(needs) {
+        return Cow::Borrowed(s);
+    }
+    let mut res = String::with_capacity(s.len() + 8);
+    for c in s.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' if quote => res.push_str("&quot;"),
+            _ => res.push(c),
+        }
+    }
+    Cow::Owned(res)
+}
+
+/// escape text content, for use between tags
+pub fn escape_text(s: &str) -> Cow<'_, str> {
+    escape_with(s, false)
+}
+
+/// escape an argument value, for use inside double quotes
+pub fn escape_attr(s: &str) -> Cow<'_, str> {
+    escape_with(s, true)
+}
+
+/// convert xml entities and character references back to characters,
+/// unknown entities are left as they are.
+pub fn unescape(s: &str) -> Cow<'_, str> {
+    if !s.contains('&') {
+        return Cow::Borrowed(s);
+    }
+    let mut res = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(n) = rest.find('&') {
+        res.push_str(&rest[..n]);
+        rest = &rest[n..];
+        let end = match rest.find(';') {
+            Some(e) => e,
+            None => break,
+        };
+        let ent = &rest[1..end];
+        let c = match ent {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match ent.strip_prefix("#x").or_else(|| ent.strip_prefix("#X")) {
+                Some(h) => u32::from_str_radix(h, 16)
+                    .ok()
+                    .and_then(std::char::from_u32),
+                None => ent
+                    .strip_prefix('#')
+                    .and_then(|d| d.parse().ok())
+                    .and_then(std::char::from_u32),
+            },
+        };
+        match c {
+            Some(c) => {
+                res.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                res.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    res.push_str(rest);
+    Cow::Owned(res)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::doc::SvgDoc;
+    use crate::*;
+
+    #[test]
+    pub fn test_args_escape() {
+        let a = Args::new()
+            .id("a&b")
+            .font_family("\"Times\"")
+            .translate("<1", "2");
+        assert_eq!(
+            &a.to_string(),
+            r#"id="a&amp;b" style="font-family:&quot;Times&quot;;" transform="translate(&lt;1,2) " "#
+        );
+    }
+
+    #[test]
+    pub fn test_args_raw() {
+        let a = Args::new().raw_arg("d", "&x;").arg("e", "&x;");
+        assert_eq!(&a.to_string(), r#"d="&x;" e="&amp;x;" "#);
+    }
+
+    #[test]
+    pub fn test_tag_escape() {
+        let t = Tag::rect(0, 0, 5, 5).class("<a>").raw_arg("href", "&ref;");
+        assert_eq!(
+            &t.to_string(),
+            r#"<rect x="0" y="0" width="5" height="5" class="&lt;a&gt;" href="&ref;" />"#
+        );
+    }
+
+    #[test]
+    pub fn test_text_escape() {
+        let mut s = String::new();
+        let mut w = SvgFmt::new(&mut s);
+        Text::new("Draw 2 & discard", 0, 0, 5)
+            .write(&mut w)
+            .unwrap();
+        Text::new("<tspan>hi</tspan>", 0, 0, 5)
+            .raw()
+            .write(&mut w)
+            .unwrap();
+        assert_eq!(
+            &s,
+            "<text x=\"0\" y=\"0\" style=\"font-size:5;\" >Draw 2 &amp; discard</text>\n\
+             <text x=\"0\" y=\"0\" style=\"font-size:5;\" ><tspan>hi</tspan></text>\n"
+        );
+    }
+
+    #[test]
+    pub fn test_write_text_escape() {
+        let mut s = String::new();
+        let mut w = SvgFmt::new(&mut s);
+        w.write_text("a < b").unwrap();
+        w.write("<b/>").unwrap();
+        assert_eq!(&s, "a &lt; b\n<b/>\n");
+    }
+
+    #[test]
+    pub fn test_doc_keeps_escapes() {
+        let mut doc = SvgDoc::new();
+        Tag::rect(0, 0, 5, 5).id("a&b").write(&mut doc).unwrap();
+        Text::new("x & y", 0, 0, 5).write(&mut doc).unwrap();
+        assert_eq!(doc.find_by_id("a&b").map(|e| &e.name[..]), Some("rect"));
+        assert_eq!(
+            doc.to_string(),
+            "<rect x=\"0\" y=\"0\" width=\"5\" height=\"5\" id=\"a&amp;b\" />\n\
+             <text x=\"0\" y=\"0\" style=\"font-size:5;\" >x &amp; y</text>\n"
+        );
+    }
+}
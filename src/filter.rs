@@ -0,0 +1,372 @@
+//! Typed filter effects.
+//!
+//! A Filter is a list of primitives, each reading the output of the one before unless
+//! given an Input. Name a primitive's output with "result" to use it later.
+//! Filters are a Resource, so they can be shared with resource::define, and are applied
+//! with the "filter" method of SvgArg, using the IdRef given back.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::filter::{Filter, Input};
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     let f = Filter::new("soft")
+//!         .blur(Input::SourceAlpha, 2)
+//!         .result("b")
+//!         .offset("b", 1, 1)
+//!         .merge(&[Input::Last, Input::SourceGraphic]);
+//!     let f = f.write_defs(&mut svg).unwrap();
+//!     Tag::rect(0, 0, 5, 5).filter(f).write(&mut svg).unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r#"<defs >
+//!   <filter id="soft" >
+//!     <feGaussianBlur in="SourceAlpha" stdDeviation="2" result="b" />
+//!     <feOffset in="b" dx="1" dy="1" />
+//!     <feMerge >
+//!       <feMergeNode />
+//!       <feMergeNode in="SourceGraphic" />
+//!     </feMerge>
+//!   </filter>
+//! </defs>
+//! <rect x="0" y="0" width="5" height="5" style="filter:url(#soft);" />
+//! "#
+//! );
+//! ```
+
+use crate::args::{Args, SvgArg};
+use crate::resource::Resource;
+use crate::tag::Tag;
+use crate::write::{SvgFmt, SvgWrite};
+use std::fmt;
+use std::fmt::Display;
+
+/// The image a primitive reads
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    /// the output of the previous primitive, "in" is not written
+    Last,
+    SourceGraphic,
+    SourceAlpha,
+    BackgroundImage,
+    BackgroundAlpha,
+    FillPaint,
+    StrokePaint,
+    /// a named result of an earlier primitive
+    Result(String),
+}
+
+impl From<&str> for Input {
+    fn from(s: &str) -> Self {
+        Input::Result(s.to_string())
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Input::Last => "",
+            Input::SourceGraphic => "SourceGraphic",
+            Input::SourceAlpha => "SourceAlpha",
+            Input::BackgroundImage => "BackgroundImage",
+            Input::BackgroundAlpha => "BackgroundAlpha",
+            Input::FillPaint => "FillPaint",
+            Input::StrokePaint => "StrokePaint",
+            Input::Result(r) => r,
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompositeOp {
+    Over,
+    In,
+    Out,
+    Atop,
+    Xor,
+    Arithmetic(f64, f64, f64, f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColorMatrix {
+    /// 20 values, 4 rows of 5
+    Matrix(Vec<f64>),
+    /// 0 is grey, 1 leaves the colors as they were
+    Saturate(f64),
+    HueRotate(f64),
+    LuminanceToAlpha,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MorphOp {
+    Erode,
+    Dilate,
+}
+
+/// A filter element, set the region it covers with x,y,width,height
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    id: String,
+    args: Args,
+    prims: Vec<Tag>,
+}
+
+fn with_in<I: Into<Input>>(t: Tag, k: &str, i: I) -> Tag {
+    match i.into() {
+        Input::Last => t,
+        i => t.arg(k, i),
+    }
+}
+
+impl Filter {
+    pub fn new<T: Display>(id: T) -> Self {
+        Filter {
+            id: id.to_string(),
+            args: Args::new(),
+            prims: Vec::new(),
+        }
+    }
+
+    /// A region of 1.5 times the size in each direction, so shadows and glows are not cut off
+    pub fn wide(self) -> Self {
+        self.xy("-25%", "-25%").wh("150%", "150%")
+    }
+
+    /// A shadow of the shape's outline, offset and blurred, drawn behind it
+    pub fn drop_shadow<T: Display, D: Display, B: Display, C: Display>(
+        id: T,
+        dx: D,
+        dy: D,
+        blur: B,
+        color: C,
+    ) -> Self {
+        Filter::new(id)
+            .wide()
+            .blur(Input::SourceAlpha, blur)
+            .offset(Input::Last, dx, dy)
+            .result("shadow")
+            .flood(color, 1)
+            .composite(Input::Last, "shadow", CompositeOp::In)
+            .merge(&[Input::Last, Input::SourceGraphic])
+    }
+
+    /// A colored blur around the shape
+    pub fn outer_glow<T: Display, R: Display, C: Display>(id: T, radius: R, color: C) -> Self {
+        Filter::new(id)
+            .wide()
+            .blur(Input::SourceAlpha, radius)
+            .result("glow")
+            .flood(color, 1)
+            .composite(Input::Last, "glow", CompositeOp::In)
+            .merge(&[Input::Last, Input::SourceGraphic])
+    }
+
+    /// Remove some of the color, 1 is fully grey
+    pub fn desaturate<T: Display>(id: T, amount: f64) -> Self {
+        Filter::new(id).color_matrix(Input::SourceGraphic, ColorMatrix::Saturate(1.0 - amount))
+    }
+
+    /// Add any primitive tag
+    pub fn primitive(mut self, t: Tag) -> Self {
+        self.prims.push(t);
+        self
+    }
+
+    /// Name the result of the last primitive, so later ones can use it as an Input
+    pub fn result<T: Display>(mut self, name: T) -> Self {
+        if let Some(t) = self.prims.pop() {
+            self.prims.push(t.arg("result", name));
+        }
+        self
+    }
+
+    pub fn blur<I: Into<Input>, S: Display>(self, i: I, std_dev: S) -> Self {
+        let t = with_in(Tag::new("feGaussianBlur"), "in", i).arg("stdDeviation", std_dev);
+        self.primitive(t)
+    }
+
+    pub fn offset<I: Into<Input>, D: Display>(self, i: I, dx: D, dy: D) -> Self {
+        let t = with_in(Tag::new("feOffset"), "in", i)
+            .arg("dx", dx)
+            .arg("dy", dy);
+        self.primitive(t)
+    }
+
+    pub fn flood<C: Display, O: Display>(self, color: C, opacity: O) -> Self {
+        let t = Tag::new("feFlood")
+            .style("flood-color", color)
+            .style("flood-opacity", opacity);
+        self.primitive(t)
+    }
+
+    pub fn composite<I: Into<Input>, I2: Into<Input>>(self, i: I, i2: I2, op: CompositeOp) -> Self {
+        let t = with_in(with_in(Tag::new("feComposite"), "in", i), "in2", i2);
+        let t = match op {
+            CompositeOp::Over => t.arg("operator", "over"),
+            CompositeOp::In => t.arg("operator", "in"),
+            CompositeOp::Out => t.arg("operator", "out"),
+            CompositeOp::Atop => t.arg("operator", "atop"),
+            CompositeOp::Xor => t.arg("operator", "xor"),
+            CompositeOp::Arithmetic(k1, k2, k3, k4) => t
+                .arg("operator", "arithmetic")
+                .arg("k1", k1)
+                .arg("k2", k2)
+                .arg("k3", k3)
+                .arg("k4", k4),
+        };
+        self.primitive(t)
+    }
+
+    /// Layer the inputs, the first at the bottom
+    pub fn merge(self, inputs: &[Input]) -> Self {
+        let mut t = Tag::new("feMerge");
+        for i in inputs {
+            t = t.child(with_in(Tag::new("feMergeNode"), "in", i.clone()));
+        }
+        self.primitive(t)
+    }
+
+    pub fn color_matrix<I: Into<Input>>(self, i: I, m: ColorMatrix) -> Self {
+        let t = with_in(Tag::new("feColorMatrix"), "in", i);
+        let t = match m {
+            ColorMatrix::Matrix(v) => {
+                let vs: Vec<String> = v.iter().map(|n| n.to_string()).collect();
+                t.arg("type", "matrix").arg("values", vs.join(" "))
+            }
+            ColorMatrix::Saturate(n) => t.arg("type", "saturate").arg("values", n),
+            ColorMatrix::HueRotate(n) => t.arg("type", "hueRotate").arg("values", n),
+            ColorMatrix::LuminanceToAlpha => t.arg("type", "luminanceToAlpha"),
+        };
+        self.primitive(t)
+    }
+
+    pub fn morphology<I: Into<Input>, R: Display>(self, i: I, op: MorphOp, radius: R) -> Self {
+        let op = match op {
+            MorphOp::Erode => "erode",
+            MorphOp::Dilate => "dilate",
+        };
+        let t = with_in(Tag::new("feMorphology"), "in", i)
+            .arg("operator", op)
+            .arg("radius", radius);
+        self.primitive(t)
+    }
+
+    /// Perlin noise, fractal noise if "fractal" is set, otherwise turbulence
+    pub fn turbulence<F: Display>(
+        self,
+        base_freq: F,
+        octaves: u32,
+        seed: u32,
+        fractal: bool,
+    ) -> Self {
+        let tp = if fractal {
+            "fractalNoise"
+        } else {
+            "turbulence"
+        };
+        let t = Tag::new("feTurbulence")
+            .arg("type", tp)
+            .arg("baseFrequency", base_freq)
+            .arg("numOctaves", octaves)
+            .arg("seed", seed);
+        self.primitive(t)
+    }
+
+    fn write_with_id<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+        let mut t = Tag::new("filter").id(id).merge(&self.args);
+        for p in &self.prims {
+            t = t.child(p.clone());
+        }
+        t.write(w)
+    }
+}
+
+impl Resource for Filter {
+    fn id_prefix(&self) -> &str {
+        &self.id
+    }
+    fn write_def<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+        self.write_with_id(id, w)
+    }
+}
+
+/// Arguments and styles go on the filter element.
+/// Transforms are ignored as a filter element has no transform,
+/// transform the element the filter is applied to instead.
+impl SvgArg for Filter {
+    fn arg<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.arg(k, v);
+        self
+    }
+    fn style<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.style(k, v);
+        self
+    }
+    fn transform<T: Display>(self, _k: &str, _args: &[T]) -> Self {
+        self
+    }
+}
+
+/// Displays as the filter element, use the IdRef from define to refer to it
+impl Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_def(&self.id, &mut SvgFmt::new(f))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::validate::Validator;
+
+    #[test]
+    pub fn test_presets_are_valid() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 10, 10).unwrap();
+            let mut defs = Tag::defs().wrap(&mut svg);
+            Filter::drop_shadow("ds", 1, 1, 2, "black")
+                .write(&mut defs)
+                .unwrap();
+            Filter::outer_glow("gl", 3, "gold")
+                .write(&mut defs)
+                .unwrap();
+            Filter::desaturate("grey", 1.0).write(&mut defs).unwrap();
+            Filter::new("noise")
+                .turbulence(0.05, 2, 7, true)
+                .morphology(Input::Last, MorphOp::Dilate, 1)
+                .composite(
+                    Input::Last,
+                    Input::SourceGraphic,
+                    CompositeOp::Arithmetic(0.0, 1.0, 1.0, 0.0),
+                )
+                .write(&mut defs)
+                .unwrap();
+        }
+        assert!(v.report().is_ok());
+        assert!(s.contains(r#"<feComposite in2="shadow" operator="in" />"#));
+        assert!(s.contains(r#"<feColorMatrix in="SourceGraphic" type="saturate" values="0" />"#));
+        assert!(s.contains(
+            r#"<feTurbulence type="fractalNoise" baseFrequency="0.05" numOctaves="2" seed="7" />"#
+        ));
+    }
+
+    #[test]
+    pub fn test_filter_with_defined_id() {
+        use crate::resource::{define, ResourceWrap};
+        let mut s = String::new();
+        {
+            let mut svg = SvgFmt::new(&mut s);
+            let mut page = ResourceWrap::new(&mut svg);
+            define(&mut page, &Filter::desaturate("f", 1.0)).unwrap();
+            let f = define(&mut page, &Filter::desaturate("f", 0.5)).unwrap();
+            Tag::rect(0, 0, 1, 1).filter(&f).write(&mut page).unwrap();
+        }
+        assert!(s.contains(r#"style="filter:url(#f_1);""#));
+        assert!(s.contains(r#"<filter id="f_1" >"#));
+    }
+}
@@ -0,0 +1,167 @@
+//! Embedding font files, so text looks the same on machines without the font installed.
+//!
+//! A FontFace is written as an @font-face rule in a style element, and displays as its
+//! family name, so it can be passed straight to font_family. Fonts added to Pages with
+//! "font" are written at the top of every page.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::font::FontFace;
+//! let face = FontFace::from_bytes("Card Title", b"wOFF....").unwrap().bold();
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     face.write(&mut svg).unwrap();
+//!     Text::new("Goblin", 0, 10, 10).font_family(&face).write(&mut svg).unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r#"<style ><![CDATA[@font-face {font-family:"Card Title";src:url(data:font/woff;base64,d09GRi4uLi4=) format("woff");font-weight:bold;}]]></style>
+//! <text x="0" y="10" style="font-family:'Card Title';font-size:10;" >Goblin</text>
+//! "#
+//! );
+//! ```
+
+use crate::image::base64;
+use crate::style::{css_string, write_css};
+use crate::write::SvgWrite;
+use std::fmt;
+use std::fmt::Display;
+use std::io;
+use std::path::Path;
+
+/// The mime type and css format of a font file, from its first bytes
+pub fn sniff_font(b: &[u8]) -> Option<(&'static str, &'static str)> {
+    match b.get(0..4)? {
+        [0, 1, 0, 0] | b"true" => Some(("font/ttf", "truetype")),
+        b"OTTO" => Some(("font/otf", "opentype")),
+        b"wOFF" => Some(("font/woff", "woff")),
+        b"wOF2" => Some(("font/woff2", "woff2")),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFace {
+    family: String,
+    src: String,
+    weight: Option<String>,
+    italic: bool,
+}
+
+impl FontFace {
+    /// Errors if the data is not a ttf, otf, woff or woff2 font
+    pub fn from_bytes<T: Display>(family: T, b: &[u8]) -> io::Result<Self> {
+        let (mime, format) = sniff_font(b)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unknown font type"))?;
+        Ok(FontFace {
+            family: family.to_string(),
+            src: format!(
+                "url(data:{};base64,{}) format(\"{}\")",
+                mime,
+                base64(b),
+                format
+            ),
+            weight: None,
+            italic: false,
+        })
+    }
+
+    pub fn load<T: Display, P: AsRef<Path>>(family: T, p: P) -> io::Result<Self> {
+        let p = p.as_ref();
+        let b = std::fs::read(p)?;
+        FontFace::from_bytes(family, &b)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", p.display(), e)))
+    }
+
+    /// The weight this file provides, such as "bold" or 300
+    pub fn weight<T: Display>(mut self, w: T) -> Self {
+        self.weight = Some(w.to_string());
+        self
+    }
+
+    pub fn bold(self) -> Self {
+        self.weight("bold")
+    }
+
+    /// This file is the italic style of the family
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    /// The @font-face rule
+    pub fn css(&self) -> String {
+        let mut res = format!(
+            "@font-face {{font-family:{};src:{};",
+            css_string(&self.family, '"'),
+            self.src
+        );
+        if let Some(ref w) = self.weight {
+            res.push_str(&format!("font-weight:{};", w));
+        }
+        if self.italic {
+            res.push_str("font-style:italic;");
+        }
+        res.push('}');
+        res
+    }
+
+    /// Write a style element with this font
+    pub fn write<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
+        write_fonts(w, std::slice::from_ref(self))
+    }
+}
+
+/// Displays as the quoted family name, for use with font_family,
+/// any quotes in the name are escaped
+impl Display for FontFace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", css_string(&self.family, '\''))
+    }
+}
+
+/// Write one style element with every font, nothing is written if there are none.
+/// As with a StyleSheet, the css is in a CDATA section.
+pub fn write_fonts<W: SvgWrite<Err = E>, E>(w: &mut W, fonts: &[FontFace]) -> Result<(), E> {
+    if fonts.is_empty() {
+        return Ok(());
+    }
+    let css: Vec<String> = fonts.iter().map(|f| f.css()).collect();
+    write_css(w, &css.join("\n"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_font_types() {
+        assert_eq!(sniff_font(&[0, 1, 0, 0, 9]), Some(("font/ttf", "truetype")));
+        assert_eq!(sniff_font(b"OTTO.."), Some(("font/otf", "opentype")));
+        assert_eq!(sniff_font(b"wOF2"), Some(("font/woff2", "woff2")));
+        assert_eq!(sniff_font(b"PNG"), None);
+        assert!(FontFace::from_bytes("x", b"\x89PNG").is_err());
+        let f = FontFace::from_bytes("Serif", b"OTTO")
+            .unwrap()
+            .weight(300)
+            .italic();
+        assert_eq!(
+            f.css(),
+            r#"@font-face {font-family:"Serif";src:url(data:font/otf;base64,T1RUTw==) format("opentype");font-weight:300;font-style:italic;}"#
+        );
+    }
+
+    #[test]
+    pub fn test_quotes_in_family() {
+        let f = FontFace::from_bytes(r#"Bob's "Font""#, b"wOF2").unwrap();
+        assert_eq!(f.to_string(), r#"'Bob\'s "Font"'"#);
+        assert!(f
+            .css()
+            .starts_with(r#"@font-face {font-family:"Bob's \"Font\"";"#));
+    }
+}
@@ -0,0 +1,283 @@
+//! Format holds the layout options shared by the writers, SvgIO and SvgFmt.
+//!
+//! The default is the original mksvg layout, two space indentation, a new line per write,
+//! and a space after every argument.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::format::{Format, Indent};
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::with_format(&mut s, Format::minified());
+//!     let mut g = Tag::g().wrap(&mut svg);
+//!     Tag::rect(0, 0, 5, 5).write(&mut g).unwrap();
+//! }
+//! assert_eq!(s, r#"<g><rect x="0" y="0" width="5" height="5"/></g>"#);
+//!
+//! let mut s = String::new();
+//! {
+//!     let f = Format::default().indent(Indent::Tab).wrap_args(20);
+//!     let mut svg = SvgFmt::with_format(&mut s, f);
+//!     let mut g = Tag::g().wrap(&mut svg);
+//!     Tag::rect(0, 0, 5, 5).write(&mut g).unwrap();
+//! }
+//! assert_eq!(s, "<g >\n\t<rect\n\t\tx=\"0\"\n\t\ty=\"0\"\n\t\twidth=\"5\"\n\t\theight=\"5\" />\n</g>\n");
+//! ```
+
+use crate::args::{Args, StyleMode};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Indent {
+    Spaces(usize),
+    Tab,
+    None,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Format {
+    pub indent: Indent,
+    /// end every write with a new line
+    pub newlines: bool,
+    /// put each argument on its own line, if the tag would be longer than this
+    pub wrap_args: Option<usize>,
+    /// write a space after every argument, as in `<rect x="0" />`
+    pub trailing_space: bool,
+    /// write styles as a style argument, or as presentation arguments
+    pub style_mode: StyleMode,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format {
+            indent: Indent::Spaces(2),
+            newlines: true,
+            wrap_args: None,
+            trailing_space: true,
+            style_mode: StyleMode::AsSet,
+        }
+    }
+}
+
+impl Format {
+    /// No indentation, new lines or extra spaces
+    pub fn minified() -> Self {
+        Format {
+            indent: Indent::None,
+            newlines: false,
+            wrap_args: None,
+            trailing_space: false,
+            style_mode: StyleMode::AsSet,
+        }
+    }
+
+    pub fn indent(mut self, i: Indent) -> Self {
+        self.indent = i;
+        self
+    }
+
+    pub fn newlines(mut self, b: bool) -> Self {
+        self.newlines = b;
+        self
+    }
+
+    pub fn wrap_args(mut self, n: usize) -> Self {
+        self.wrap_args = Some(n);
+        self
+    }
+
+    pub fn no_trailing_space(mut self) -> Self {
+        self.trailing_space = false;
+        self
+    }
+
+    /// Write styles as presentation arguments, or the reverse, Args::style_mode
+    /// overrides this for a single element.
+    ///
+    /// ```
+    /// use mksvg::*;
+    /// use mksvg::args::StyleMode;
+    /// use mksvg::format::Format;
+    /// let mut s = String::new();
+    /// {
+    ///     let f = Format::default().style_mode(StyleMode::Attributes);
+    ///     let mut svg = SvgFmt::with_format(&mut s, f);
+    ///     Tag::rect(0, 0, 5, 5).fill("red").write(&mut svg).unwrap();
+    /// }
+    /// assert_eq!(s, "<rect x=\"0\" y=\"0\" width=\"5\" height=\"5\" fill=\"red\" />\n");
+    /// ```
+    pub fn style_mode(mut self, m: StyleMode) -> Self {
+        self.style_mode = m;
+        self
+    }
+
+    /// the padding for a given depth
+    pub fn pad(&self, depth: i8) -> String {
+        let unit = match self.indent {
+            Indent::Spaces(n) => " ".repeat(n),
+            Indent::Tab => "\t".to_string(),
+            Indent::None => return String::new(),
+        };
+        unit.repeat(depth.max(0) as usize)
+    }
+
+    /// the full output for one write at the given depth, only the first line is padded,
+    /// so text content is kept as it is.
+    pub fn line(&self, depth: i8, s: &str) -> String {
+        let mut res = format!("{}{}", self.pad(depth), s);
+        if self.newlines {
+            res.push('\n');
+        }
+        res
+    }
+
+    /// Build a tag, "end" should be ">" or "/>". depth is the depth the tag is written
+    /// at, wrapped arguments are padded one deeper.
+    pub fn tag(&self, depth: i8, name: &str, args: &Args, end: &str) -> String {
+        let parts = args.parts(name, !self.trailing_space, self.style_mode);
+        if let Some(mx) = self.wrap_args {
+            let len: usize = parts.iter().map(|p| p.len() + 1).sum();
+            if parts.len() > 1 && name.len() + len + end.len() + 2 > mx {
+                let mut res = format!("<{}", name);
+                for p in &parts {
+                    res.push('\n');
+                    res.push_str(&self.pad(depth + 1));
+                    res.push_str(p);
+                }
+                if self.trailing_space {
+                    res.push(' ');
+                }
+                res.push_str(end);
+                return res;
+            }
+        }
+        let mut res = format!("<{}", name);
+        match self.trailing_space {
+            true => {
+                res.push(' ');
+                for p in &parts {
+                    res.push_str(p);
+                    res.push(' ');
+                }
+            }
+            false => {
+                for p in &parts {
+                    res.push(' ');
+                    res.push_str(p);
+                }
+            }
+        }
+        res.push_str(end);
+        res
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    pub fn test_minified_page() {
+        let mut s = String::new();
+        {
+            let mut w = SvgFmt::with_format(&mut s, Format::minified());
+            let mut svg = Tag::start(&mut w, 10, 10).unwrap();
+            let mut g = Tag::g().translate(1, 2).wrap(&mut svg);
+            Text::new("hi", 0, 0, 5).write(&mut g).unwrap();
+        }
+        assert_eq!(
+            s,
+            r#"<?xml version="1.0" ?><svg width="10" height="10" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"><g transform="translate(1,2)"><text x="0" y="0" style="font-size:5;">hi</text></g></svg>"#
+        );
+    }
+
+    #[test]
+    pub fn test_io_indent() {
+        let mut v: Vec<u8> = Vec::new();
+        {
+            let f = Format::default()
+                .indent(Indent::Spaces(4))
+                .no_trailing_space();
+            let mut w = SvgIO::with_format(&mut v, f);
+            let mut g = Tag::g().wrap(&mut w);
+            Tag::rect(0, 0, 5, 5).write(&mut g).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(v).unwrap(),
+            "<g>\n    <rect x=\"0\" y=\"0\" width=\"5\" height=\"5\"/>\n</g>\n"
+        );
+    }
+
+    #[test]
+    pub fn test_style_modes() {
+        let mut s = String::new();
+        {
+            let f = Format::default().style_mode(StyleMode::Attributes);
+            let mut w = SvgFmt::with_format(&mut s, f);
+            Tag::rect(0, 0, 5, 5)
+                .arg("fill", "blue")
+                .fill("red")
+                .stroke_width(2)
+                .style("mix-blend-mode", "multiply")
+                .write(&mut w)
+                .unwrap();
+            Tag::circle(0, 0, 3)
+                .arg("stroke", "black")
+                .fill("red")
+                .style_mode(StyleMode::Styles)
+                .write(&mut w)
+                .unwrap();
+            Tag::line(0, 0, 1, 1)
+                .stroke("black")
+                .style_mode(StyleMode::AsSet)
+                .write(&mut w)
+                .unwrap();
+        }
+        assert_eq!(
+            s,
+            r#"<rect x="0" y="0" width="5" height="5" fill="red" stroke-width="2" style="mix-blend-mode:multiply;" />
+<circle cx="0" cy="0" r="3" style="stroke:black;fill:red;" />
+<line x1="0" y1="0" x2="1" y2="1" style="stroke:black;" />
+"#
+        );
+    }
+
+    #[test]
+    pub fn test_wrap_args_keeps_content() {
+        use crate::gradient::LinearGradient;
+        use crate::resource::{define, ResourceWrap};
+        use crate::style::{Rule, StyleSheet};
+        let mut s = String::new();
+        {
+            let mut w = SvgFmt::with_format(&mut s, Format::default().wrap_args(10));
+            let mut g = Tag::g().wrap(&mut w);
+            Tag::text_tag(0, 0).text("a\nb").write(&mut g).unwrap();
+            StyleSheet::new()
+                .rule(Rule::for_class("a").fill("red"))
+                .rule(Rule::for_class("b").fill("blue"))
+                .write(&mut g)
+                .unwrap();
+            let mut page = ResourceWrap::new(&mut g);
+            let grad = LinearGradient::new("g").x2(0).y2(1);
+            define(&mut page, &grad).unwrap();
+        }
+        assert_eq!(
+            s,
+            r#"<g >
+  <text
+    x="0"
+    y="0" >a
+b</text>
+  <style ><![CDATA[.a {fill:red;}
+.b {fill:blue;}]]></style>
+  <defs >
+    <linearGradient
+      id="g"
+      x2="0"
+      y2="1" />
+  </defs>
+</g>
+"#
+        );
+    }
+}
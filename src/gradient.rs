@@ -0,0 +1,288 @@
+//! Typed linear and radial gradients.
+//!
+//! A gradient is written into the defs of a document, and gives back an IdRef that can
+//! be used directly as a fill or stroke. Gradients are also a Resource, so
+//! resource::define will share them between the cards on a page. Always paint with
+//! the IdRef given back, as the id may have been numbered to keep it unique.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::gradient::{LinearGradient, Spread};
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     let grad = LinearGradient::new("frame")
+//!         .x2(0).y2(1)
+//!         .stop(0, "gold")
+//!         .stop_opacity(1, "black", 0.5)
+//!         .spread(Spread::Reflect);
+//!     let paint = grad.write_defs(&mut svg).unwrap();
+//!     Tag::rect(0, 0, 5, 5).fill(paint).write(&mut svg).unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r#"<defs >
+//!   <linearGradient id="frame" x2="0" y2="1" spreadMethod="reflect" >
+//!     <stop offset="0" style="stop-color:gold;" />
+//!     <stop offset="1" style="stop-color:black;stop-opacity:0.5;" />
+//!   </linearGradient>
+//! </defs>
+//! <rect x="0" y="0" width="5" height="5" style="fill:url(#frame);" />
+//! "#
+//! );
+//! ```
+
+use crate::args::{Args, SvgArg};
+pub use crate::resource::IdRef;
+use crate::resource::Resource;
+use crate::tag::Tag;
+use crate::write::{SvgFmt, SvgWrite};
+use std::fmt;
+use std::fmt::Display;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Spread {
+    Pad,
+    Reflect,
+    Repeat,
+}
+
+impl Display for Spread {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Spread::Pad => "pad",
+            Spread::Reflect => "reflect",
+            Spread::Repeat => "repeat",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The coordinate system for gradientUnits, and the other *Units arguments
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Units {
+    UserSpaceOnUse,
+    ObjectBoundingBox,
+}
+
+impl Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Units::UserSpaceOnUse => "userSpaceOnUse",
+            Units::ObjectBoundingBox => "objectBoundingBox",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stop {
+    offset: String,
+    color: String,
+    opacity: Option<String>,
+}
+
+impl Stop {
+    fn tag(&self) -> Tag {
+        let t = Tag::new("stop")
+            .arg("offset", &self.offset)
+            .style("stop-color", &self.color);
+        match self.opacity {
+            Some(ref o) => t.style("stop-opacity", o),
+            None => t,
+        }
+    }
+}
+
+/// The parts common to both gradient types
+#[derive(Clone, Debug, PartialEq)]
+struct Grad {
+    name: &'static str,
+    id: String,
+    args: Args,
+    trans: Args,
+    stops: Vec<Stop>,
+}
+
+impl Grad {
+    fn new<T: Display>(name: &'static str, id: T) -> Self {
+        Grad {
+            name,
+            id: id.to_string(),
+            args: Args::new(),
+            trans: Args::new(),
+            stops: Vec::new(),
+        }
+    }
+
+    fn write_with_id<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+        let mut t = Tag::new(self.name).id(id).merge(&self.args);
+        let gt: Vec<String> = self
+            .trans
+            .transforms()
+            .map(|(k, v)| format!("{}({})", k, v))
+            .collect();
+        if !gt.is_empty() {
+            t = t.arg("gradientTransform", gt.join(" "));
+        }
+        if self.stops.is_empty() {
+            return t.write(w);
+        }
+        let mut g = t.wrap(w);
+        for s in &self.stops {
+            s.tag().write(&mut g)?;
+        }
+        g.finish()
+    }
+}
+
+macro_rules! gradient_impl {
+    ($t:ident) => {
+        impl $t {
+            /// add a color stop, offset is 0..1 or a percentage
+            pub fn stop<O: Display, C: Display>(mut self, offset: O, color: C) -> Self {
+                self.0.stops.push(Stop {
+                    offset: offset.to_string(),
+                    color: color.to_string(),
+                    opacity: None,
+                });
+                self
+            }
+
+            pub fn stop_opacity<O: Display, C: Display, A: Display>(
+                mut self,
+                offset: O,
+                color: C,
+                opacity: A,
+            ) -> Self {
+                self.0.stops.push(Stop {
+                    offset: offset.to_string(),
+                    color: color.to_string(),
+                    opacity: Some(opacity.to_string()),
+                });
+                self
+            }
+
+            pub fn spread(self, s: Spread) -> Self {
+                self.arg("spreadMethod", s)
+            }
+
+            pub fn units(self, u: Units) -> Self {
+                self.arg("gradientUnits", u)
+            }
+        }
+
+        /// The id given to new is used if free, otherwise it is numbered
+        impl Resource for $t {
+            fn id_prefix(&self) -> &str {
+                &self.0.id
+            }
+            fn write_def<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+                self.0.write_with_id(id, w)
+            }
+        }
+
+        /// Transforms are written as the gradientTransform
+        impl SvgArg for $t {
+            fn arg<T: Display>(mut self, k: &str, v: T) -> Self {
+                self.0.args = self.0.args.arg(k, v);
+                self
+            }
+            fn style<T: Display>(mut self, k: &str, v: T) -> Self {
+                self.0.args = self.0.args.style(k, v);
+                self
+            }
+            fn transform<T: Display>(mut self, k: &str, args: &[T]) -> Self {
+                self.0.trans = self.0.trans.transform(k, args);
+                self
+            }
+        }
+
+        /// Displays as the gradient element, use the IdRef from define to refer to it
+        impl Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.write_def(self.id_prefix(), &mut SvgFmt::new(f))
+            }
+        }
+    };
+}
+
+/// A linearGradient, set the direction with x1,y1,x2,y2
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearGradient(Grad);
+
+impl LinearGradient {
+    pub fn new<T: Display>(id: T) -> Self {
+        LinearGradient(Grad::new("linearGradient", id))
+    }
+}
+
+gradient_impl!(LinearGradient);
+
+/// A radialGradient, set the circle with cx,cy,r and the focus with "focus"
+#[derive(Clone, Debug, PartialEq)]
+pub struct RadialGradient(Grad);
+
+impl RadialGradient {
+    pub fn new<T: Display>(id: T) -> Self {
+        RadialGradient(Grad::new("radialGradient", id))
+    }
+
+    pub fn focus<T: Display>(self, fx: T, fy: T) -> Self {
+        self.arg("fx", fx).arg("fy", fy)
+    }
+}
+
+gradient_impl!(RadialGradient);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::validate::Validator;
+    use crate::write::SvgFmt;
+
+    #[test]
+    pub fn test_radial_gradient() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        let g = RadialGradient::new("glow")
+            .cx("50%")
+            .cy("50%")
+            .r("50%")
+            .focus(0.3, 0.3)
+            .units(Units::ObjectBoundingBox)
+            .rotate(45, 0, 0)
+            .stop(0, "white")
+            .stop("100%", "blue");
+        {
+            let mut svg = Tag::start(&mut v, 10, 10).unwrap();
+            let p = g.write_defs(&mut svg).unwrap();
+            Tag::circle(5, 5, 5).stroke(&p).write(&mut svg).unwrap();
+        }
+        assert!(v.report().is_ok());
+        assert!(s.contains(
+            r#"<radialGradient id="glow" cx="50%" cy="50%" r="50%" fx="0.3" fy="0.3" gradientUnits="objectBoundingBox" gradientTransform="rotate(45,0,0)" >"#
+        ));
+        assert!(s.contains(r#"style="stroke:url(#glow);""#));
+    }
+
+    #[test]
+    pub fn test_paint_with_defined_id() {
+        use crate::resource::{define, ResourceWrap};
+        let a = LinearGradient::new("g").stop(0, "red");
+        let b = LinearGradient::new("g").stop(0, "blue");
+        let mut s = String::new();
+        {
+            let mut svg = SvgFmt::new(&mut s);
+            let mut page = ResourceWrap::new(&mut svg);
+            let pa = define(&mut page, &a).unwrap();
+            let pb = define(&mut page, &b).unwrap();
+            Tag::rect(0, 0, 1, 1).fill(&pa).write(&mut page).unwrap();
+            Tag::rect(0, 0, 1, 1).fill(&pb).write(&mut page).unwrap();
+        }
+        assert!(s.contains(r#"style="fill:url(#g);""#));
+        assert!(s.contains(r#"style="fill:url(#g_1);""#));
+        assert!(s.contains(r#"<linearGradient id="g_1" >"#));
+        assert!(b.to_string().starts_with(r#"<linearGradient id="g" >"#));
+    }
+}
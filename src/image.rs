@@ -0,0 +1,284 @@
+//! Embedding image files in the svg as data URIs, so pages still work when moved.
+//!
+//! The type of the image is read from its first bytes, and the size from its header,
+//! so a width or height can be left out and the other is set to keep the aspect ratio.
+//! Errors reading the file are io::Errors, and so can be returned by any writer whose
+//! error type can be made from one, such as SvgIO and SvgDoc.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::image::Embedded;
+//! // a 2x1 gif
+//! let gif = b"GIF89a\x02\x00\x01\x00\x00\x00\x00;";
+//! let e = Embedded::from_bytes(gif).unwrap();
+//! assert_eq!(e.mime(), "image/gif");
+//! assert_eq!(e.size(), Some((2., 1.)));
+//! assert_eq!(
+//!     e.tag(0, 0, Some(10.), None).to_string(),
+//!     r#"<image x="0" y="0" width="10" height="5" href="data:image/gif;base64,R0lGODlhAgABAAAAADs=" />"#
+//! );
+//! ```
+
+use crate::args::SvgArg;
+use crate::parse::parse_str;
+use crate::tag::Tag;
+use crate::write::SvgWrite;
+use std::convert::TryInto;
+use std::fmt::Display;
+use std::io;
+use std::path::Path;
+
+const B64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// standard base64, with padding
+pub fn base64(data: &[u8]) -> String {
+    let mut res = String::with_capacity(data.len().div_ceil(3) * 4);
+    for ch in data.chunks(3) {
+        let b = [ch[0], *ch.get(1).unwrap_or(&0), *ch.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            match i <= ch.len() {
+                true => res.push(B64[(n >> (18 - 6 * i) & 63) as usize] as char),
+                false => res.push('='),
+            }
+        }
+    }
+    res
+}
+
+fn be16(b: &[u8], i: usize) -> Option<f64> {
+    Some(u16::from_be_bytes([*b.get(i)?, *b.get(i + 1)?]) as f64)
+}
+
+fn le16(b: &[u8], i: usize) -> Option<f64> {
+    Some(u16::from_le_bytes([*b.get(i)?, *b.get(i + 1)?]) as f64)
+}
+
+fn le24(b: &[u8], i: usize) -> Option<f64> {
+    Some(u32::from_le_bytes([*b.get(i)?, *b.get(i + 1)?, *b.get(i + 2)?, 0]) as f64)
+}
+
+fn jpeg_size(b: &[u8]) -> Option<(f64, f64)> {
+    let mut i = 2;
+    while i + 9 < b.len() {
+        if b[i] != 0xFF {
+            return None;
+        }
+        let m = b[i + 1];
+        if (0xC0..=0xCF).contains(&m) && m != 0xC4 && m != 0xC8 && m != 0xCC {
+            return Some((be16(b, i + 7)?, be16(b, i + 5)?));
+        }
+        i += 2 + be16(b, i + 2)? as usize;
+    }
+    None
+}
+
+fn webp_size(b: &[u8]) -> Option<(f64, f64)> {
+    match b.get(12..16)? {
+        b"VP8 " => Some((
+            (le16(b, 26)? as u16 & 0x3FFF) as f64,
+            (le16(b, 28)? as u16 & 0x3FFF) as f64,
+        )),
+        b"VP8L" => {
+            let v = u32::from_le_bytes([*b.get(21)?, *b.get(22)?, *b.get(23)?, *b.get(24)?]);
+            Some(((v & 0x3FFF) as f64 + 1., (v >> 14 & 0x3FFF) as f64 + 1.))
+        }
+        b"VP8X" => Some((le24(b, 24)? + 1., le24(b, 27)? + 1.)),
+        _ => None,
+    }
+}
+
+/// a length without units, or in px
+fn svg_len(s: &str) -> Option<f64> {
+    s.trim().trim_end_matches("px").parse().ok()
+}
+
+fn svg_size(b: &[u8]) -> Option<(f64, f64)> {
+    let doc = parse_str(std::str::from_utf8(b).ok()?).ok()?;
+    let root = doc.find_by_name("svg").into_iter().next()?;
+    if let (Some(w), Some(h)) = (root.get("width"), root.get("height")) {
+        if let (Some(w), Some(h)) = (svg_len(w), svg_len(h)) {
+            return Some((w, h));
+        }
+    }
+    let vb: Vec<f64> = root
+        .get("viewBox")?
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter_map(|v| v.parse().ok())
+        .collect();
+    match vb.len() {
+        4 => Some((vb[2], vb[3])),
+        _ => None,
+    }
+}
+
+fn is_svg(b: &[u8]) -> bool {
+    let start = &b[..b.len().min(1024)];
+    String::from_utf8_lossy(start).contains("<svg")
+}
+
+/// The mime type of the image, from its first bytes
+pub fn sniff_mime(b: &[u8]) -> Option<&'static str> {
+    if b.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some("image/png");
+    }
+    if b.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some("image/jpeg");
+    }
+    if b.starts_with(b"GIF87a") || b.starts_with(b"GIF89a") {
+        return Some("image/gif");
+    }
+    if b.starts_with(b"RIFF") && b.get(8..12) == Some(b"WEBP") {
+        return Some("image/webp");
+    }
+    if is_svg(b) {
+        return Some("image/svg+xml");
+    }
+    None
+}
+
+/// The width and height from the image header
+pub fn sniff_size(b: &[u8]) -> Option<(f64, f64)> {
+    match sniff_mime(b)? {
+        "image/png" => Some((
+            u32::from_be_bytes(b.get(16..20)?.try_into().ok()?) as f64,
+            u32::from_be_bytes(b.get(20..24)?.try_into().ok()?) as f64,
+        )),
+        "image/jpeg" => jpeg_size(b),
+        "image/gif" => Some((le16(b, 6)?, le16(b, 8)?)),
+        "image/webp" => webp_size(b),
+        _ => svg_size(b),
+    }
+}
+
+/// An image as a data URI
+#[derive(Clone, Debug, PartialEq)]
+pub struct Embedded {
+    mime: &'static str,
+    uri: String,
+    size: Option<(f64, f64)>,
+}
+
+impl Embedded {
+    /// Errors if the data is not a png, jpeg, gif, webp or svg image
+    pub fn from_bytes(b: &[u8]) -> io::Result<Self> {
+        let mime = sniff_mime(b)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unknown image type"))?;
+        Ok(Embedded {
+            mime,
+            uri: format!("data:{};base64,{}", mime, base64(b)),
+            size: sniff_size(b),
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(p: P) -> io::Result<Self> {
+        let p = p.as_ref();
+        let b = std::fs::read(p)?;
+        Embedded::from_bytes(&b)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", p.display(), e)))
+    }
+
+    pub fn mime(&self) -> &str {
+        self.mime
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// The natural size of the image, if it could be read from the header
+    pub fn size(&self) -> Option<(f64, f64)> {
+        self.size
+    }
+
+    /// An image tag, a missing width or height is set to keep the aspect ratio,
+    /// if both are missing the natural size is used.
+    pub fn tag<P: Display>(&self, x: P, y: P, w: Option<f64>, h: Option<f64>) -> Tag {
+        let (w, h) = match (w, h, self.size) {
+            (Some(w), None, Some((nw, nh))) if nw > 0. => (Some(w), Some(w * nh / nw)),
+            (None, Some(h), Some((nw, nh))) if nh > 0. => (Some(h * nw / nh), Some(h)),
+            (None, None, Some((nw, nh))) => (Some(nw), Some(nh)),
+            (w, h, _) => (w, h),
+        };
+        let mut t = Tag::new("image").xy(x, y);
+        if let Some(w) = w {
+            t = t.width(w);
+        }
+        if let Some(h) = h {
+            t = t.height(h);
+        }
+        t.href(&self.uri)
+    }
+}
+
+/// Load the image at path and write it, embedded, see Embedded::tag for the sizing
+pub fn write_embedded<W, E, P, Q>(
+    w: &mut W,
+    path: Q,
+    x: P,
+    y: P,
+    width: Option<f64>,
+    height: Option<f64>,
+) -> Result<(), E>
+where
+    W: SvgWrite<Err = E>,
+    E: From<io::Error>,
+    P: Display,
+    Q: AsRef<Path>,
+{
+    let e = Embedded::load(path)?;
+    e.tag(x, y, width, height).write(w)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::err::DocError;
+    use crate::write::SvgIO;
+
+    #[test]
+    pub fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    pub fn test_sniff_sizes() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&[0, 0, 1, 0, 0, 0, 0, 50]);
+        assert_eq!(sniff_size(&png), Some((256., 50.)));
+        let jpg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0, 4, 0, 0, 0xFF, 0xC0, 0, 11, 8, 0, 30, 0, 40, 3, 0, 0,
+        ];
+        assert_eq!(sniff_size(&jpg), Some((40., 30.)));
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X".to_vec();
+        webp.extend_from_slice(&[0; 8]);
+        webp.extend_from_slice(&[99, 0, 0, 49, 0, 0]);
+        assert_eq!(sniff_size(&webp), Some((100., 50.)));
+        let svg = br#"<?xml version="1.0" ?><svg viewBox="0 0 20 10"></svg>"#;
+        assert_eq!(sniff_mime(svg), Some("image/svg+xml"));
+        assert_eq!(sniff_size(svg), Some((20., 10.)));
+        assert_eq!(sniff_mime(b"hello"), None);
+    }
+
+    #[test]
+    pub fn test_missing_file_error() {
+        let mut v: Vec<u8> = Vec::new();
+        let r = write_embedded(
+            &mut SvgIO::new(&mut v),
+            "/no/such/image.png",
+            0,
+            0,
+            None,
+            None,
+        );
+        assert!(r.is_err());
+        let mut doc = crate::doc::SvgDoc::new();
+        let r: Result<(), DocError> =
+            write_embedded(&mut doc, "/no/such/image.png", 0, 0, Some(5.), None);
+        assert!(matches!(r, Err(DocError::IO(_))));
+    }
+}
@@ -39,11 +39,7 @@ where
             self.t_sofar = 0;
         }
         self.t_sofar += 1;
-        match self.curr.as_ref() {
-            Some(rv) => Some(rv.clone()),
-
-            None => None,
-        }
+        self.curr.clone()
     }
 }
 
@@ -9,20 +9,47 @@
 //!
 //!the "text" mod provides some wrapping utilities for printing multiple lines of text in svg
 //!
+//!the "doc" mod provides SvgDoc, an SvgWrite that keeps the written svg as a tree, so it can
+//!be searched and modified before being written out, the "parse" mod reads existing files into one.
+//!
+//!the "resource" mod gives each page a registry of shared definitions, such as gradients,
+//!with unique ids, written once in a single defs element.
+//!
 
+pub mod anim;
 pub mod args;
+pub mod clip;
+pub mod color;
+pub mod doc;
 pub mod err;
+pub mod escape;
+pub mod filter;
+pub mod font;
+pub mod format;
+pub mod gradient;
+pub mod image;
 pub mod iter;
+pub mod marker;
+pub mod ns;
 //pub mod macros;
 pub mod page;
+pub mod parse;
 pub mod path;
+pub mod resource;
+pub mod style;
+pub mod symbol;
 pub mod tag;
+pub mod testing;
 pub mod text;
+pub mod transform;
 pub mod unit;
+pub mod validate;
 pub mod write;
 
 pub use crate::args::{Args, SvgArg};
+pub use crate::doc::SvgDoc;
 pub use crate::path::PathD;
+pub use crate::resource::Resource;
 pub use crate::tag::Tag;
 pub use crate::text::{wrap, Text};
 pub use crate::write::{SvgFmt, SvgIO, SvgWrite};
@@ -0,0 +1,207 @@
+//! Markers, for arrowheads and other decorations on the ends and corners of lines.
+//!
+//! The built in shapes are drawn in a 10 by 10 viewBox, use "size" to set how big
+//! they appear, by default in units of the line's stroke width. Markers are a Resource,
+//! and are placed with marker_start, marker_mid and marker_end from SvgArg, using the
+//! IdRef given back when they are written.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::marker::Marker;
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     let m = Marker::arrow("arrow").size(4, 4).fill("red");
+//!     let m = m.write_defs(&mut svg).unwrap();
+//!     Tag::line(0, 0, 20, 0).stroke("black").marker_end(m).write(&mut svg).unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r#"<defs >
+//!   <marker id="arrow" viewBox="0 0 10 10" orient="auto" refX="10" refY="5" markerWidth="4" markerHeight="4" style="fill:red;" >
+//!     <path d="M 0 0 L 10 5 L 0 10 Z  " />
+//!   </marker>
+//! </defs>
+//! <line x1="0" y1="0" x2="20" y2="0" style="stroke:black;marker-end:url(#arrow);" />
+//! "#
+//! );
+//! ```
+
+use crate::args::{Args, SvgArg};
+use crate::path::PathD;
+use crate::resource::Resource;
+use crate::tag::Tag;
+use crate::write::{SvgFmt, SvgWrite};
+use std::fmt;
+use std::fmt::Display;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Orient {
+    /// follow the direction of the line
+    Auto,
+    /// as Auto, but turned round at the start of the line
+    AutoStartReverse,
+    /// a fixed angle in degrees
+    Angle(f64),
+}
+
+impl Display for Orient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Orient::Auto => write!(f, "auto"),
+            Orient::AutoStartReverse => write!(f, "auto-start-reverse"),
+            Orient::Angle(a) => write!(f, "{}", a),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marker {
+    id: String,
+    args: Args,
+    shape: Tag,
+}
+
+impl Marker {
+    /// A marker drawing shape, in a viewBox of 0 0 w h, with the reference point at 0,0
+    pub fn new<T: Display, S: Display>(id: T, shape: Tag, w: S, h: S) -> Self {
+        Marker {
+            id: id.to_string(),
+            args: Args::new().arg("viewBox", format!("0 0 {} {}", w, h)),
+            shape,
+        }
+        .orient(Orient::Auto)
+    }
+
+    /// A marker from a path, in a viewBox of 0 0 w h
+    pub fn custom<T: Display, S: Display>(id: T, p: PathD, w: S, h: S) -> Self {
+        Marker::new(id, Tag::path(p), w, h)
+    }
+
+    /// A triangle, with its point at the end of the line
+    pub fn arrow<T: Display>(id: T) -> Self {
+        let p = PathD::abs().m(0, 0).l(10, 5).l(0, 10).z();
+        Marker::custom(id, p, 10, 10).ref_xy(10, 5)
+    }
+
+    /// A circle, centered on the point
+    pub fn circle<T: Display>(id: T) -> Self {
+        Marker::new(id, Tag::circle(5, 5, 5), 10, 10).ref_xy(5, 5)
+    }
+
+    /// A square, centered on the point
+    pub fn square<T: Display>(id: T) -> Self {
+        Marker::new(id, Tag::rect(0, 0, 10, 10), 10, 10).ref_xy(5, 5)
+    }
+
+    /// The point of the marker that is placed on the line, in viewBox units
+    pub fn ref_xy<T: Display>(self, x: T, y: T) -> Self {
+        self.arg("refX", x).arg("refY", y)
+    }
+
+    pub fn orient(self, o: Orient) -> Self {
+        self.arg("orient", o)
+    }
+
+    /// The displayed size, in multiples of the stroke width unless user_units is set
+    pub fn size<T: Display>(self, w: T, h: T) -> Self {
+        self.arg("markerWidth", w).arg("markerHeight", h)
+    }
+
+    /// Size the marker in user units, instead of by the stroke width
+    pub fn user_units(self) -> Self {
+        self.arg("markerUnits", "userSpaceOnUse")
+    }
+
+    fn write_with_id<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+        Tag::marker(id)
+            .merge(&self.args)
+            .child(self.shape.clone())
+            .write(w)
+    }
+}
+
+impl Resource for Marker {
+    fn id_prefix(&self) -> &str {
+        &self.id
+    }
+    fn write_def<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+        self.write_with_id(id, w)
+    }
+}
+
+/// Styles such as fill and stroke are inherited by the shape,
+/// transforms apply to the shape itself
+impl SvgArg for Marker {
+    fn arg<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.arg(k, v);
+        self
+    }
+    fn style<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.style(k, v);
+        self
+    }
+    fn transform<T: Display>(mut self, k: &str, args: &[T]) -> Self {
+        self.shape = self.shape.transform(k, args);
+        self
+    }
+}
+
+/// Displays as the marker element, use the IdRef from define to refer to it
+impl Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_with_id(&self.id, &mut SvgFmt::new(f))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resource::{define, ResourceWrap};
+    use crate::validate::Validator;
+
+    #[test]
+    pub fn test_markers_on_polyline() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 50, 50).unwrap();
+            let mut page = ResourceWrap::new(&mut svg);
+            let dot = define(&mut page, &Marker::circle("dot").size(2, 2)).unwrap();
+            let sq = define(&mut page, &Marker::square("sq").orient(Orient::Angle(45.0))).unwrap();
+            let head = Marker::arrow("head").orient(Orient::AutoStartReverse);
+            let head = define(&mut page, &head).unwrap();
+            Tag::polyline(&[(0, 0), (10, 10), (20, 0)])
+                .marker_start(&head)
+                .marker_mid(&dot)
+                .marker_end(&sq)
+                .write(&mut page)
+                .unwrap();
+        }
+        assert!(v.report().is_ok());
+        assert!(s.contains(
+            r#"style="marker-start:url(#head);marker-mid:url(#dot);marker-end:url(#sq);""#
+        ));
+        assert!(s.contains(r#"orient="auto-start-reverse""#));
+        assert!(
+            s.contains(r#"<marker id="sq" viewBox="0 0 10 10" orient="45" refX="5" refY="5" >"#)
+        );
+    }
+
+    #[test]
+    pub fn test_marker_with_defined_id() {
+        let mut s = String::new();
+        {
+            let mut svg = SvgFmt::new(&mut s);
+            let mut page = ResourceWrap::new(&mut svg);
+            define(&mut page, &Marker::circle("dot")).unwrap();
+            let big = define(&mut page, &Marker::circle("dot").size(4, 4)).unwrap();
+            Tag::line(0, 0, 9, 0)
+                .marker_end(&big)
+                .write(&mut page)
+                .unwrap();
+        }
+        assert!(s.contains(r#"style="marker-end:url(#dot_1);""#));
+        assert!(s.contains(r#"<marker id="dot_1" "#));
+    }
+}
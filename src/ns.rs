@@ -0,0 +1,98 @@
+//! The extra xml namespaces used by editors such as inkscape.
+//!
+//! When a Tag uses one of these prefixes, in its name, its arguments or its children,
+//! the namespace is declared on that tag, unless a tag it is written inside already
+//! declared it. Tag::start_ns declares them on the root svg element instead.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::ns::Ns;
+//! let mut s = String::new();
+//! {
+//!     let mut w = SvgFmt::new(&mut s);
+//!     let mut svg = Tag::start_ns(&mut w, 10, 10, &[Ns::Inkscape]).unwrap();
+//!     Tag::new("sodipodi:namedview").write(&mut svg).unwrap();
+//!     Tag::g().arg("inkscape:label", "cards").write(&mut svg).unwrap();
+//! }
+//! assert!(s.contains(r#"<sodipodi:namedview xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd" />"#));
+//! assert!(s.contains(r#"<g inkscape:label="cards" />"#));
+//! ```
+
+use crate::args::Args;
+use crate::doc::Node;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Ns {
+    Inkscape,
+    Sodipodi,
+    Rdf,
+    Dc,
+    Cc,
+}
+
+pub const ALL: &[Ns] = &[Ns::Inkscape, Ns::Sodipodi, Ns::Rdf, Ns::Dc, Ns::Cc];
+
+impl Ns {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Ns::Inkscape => "inkscape",
+            Ns::Sodipodi => "sodipodi",
+            Ns::Rdf => "rdf",
+            Ns::Dc => "dc",
+            Ns::Cc => "cc",
+        }
+    }
+
+    pub fn uri(&self) -> &'static str {
+        match self {
+            Ns::Inkscape => "http://www.inkscape.org/namespaces/inkscape",
+            Ns::Sodipodi => "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd",
+            Ns::Rdf => "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+            Ns::Dc => "http://purl.org/dc/elements/1.1/",
+            Ns::Cc => "http://creativecommons.org/ns#",
+        }
+    }
+
+    pub fn from_prefix(p: &str) -> Option<Ns> {
+        ALL.iter().find(|n| n.prefix() == p).copied()
+    }
+
+    /// the Ns of a prefixed name such as "inkscape:label"
+    pub fn of_name(name: &str) -> Option<Ns> {
+        let (p, _) = name.split_once(':')?;
+        Ns::from_prefix(p)
+    }
+}
+
+fn add(found: &mut Vec<Ns>, n: Option<Ns>) {
+    if let Some(n) = n {
+        if !found.contains(&n) {
+            found.push(n);
+        }
+    }
+}
+
+fn used_in(name: &str, args: &Args, found: &mut Vec<Ns>) {
+    add(found, Ns::of_name(name));
+    for (k, _) in args.args() {
+        if !k.starts_with("xmlns:") {
+            add(found, Ns::of_name(k));
+        }
+    }
+}
+
+/// every known namespace used by an element and its children, in order of use
+pub(crate) fn used(name: &str, args: &Args, children: &[Node]) -> Vec<Ns> {
+    let mut res = Vec::new();
+    used_tree(name, args, children, &mut res);
+    res
+}
+
+fn used_tree(name: &str, args: &Args, children: &[Node], found: &mut Vec<Ns>) {
+    used_in(name, args, found);
+    for c in children {
+        if let Node::Elem(e) = c {
+            used_tree(&e.name, &e.args, &e.children, found);
+        }
+    }
+}
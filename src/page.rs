@@ -1,5 +1,9 @@
 use crate::args::SvgArg;
 //use crate::err::PageError;
+use crate::font::{write_fonts, FontFace};
+use crate::resource::{define, ResourceWrap};
+use crate::style::StyleSheet;
+use crate::symbol::Symbol;
 use crate::tag::Tag;
 use crate::unit::px;
 use crate::write::{qcast, CDNum, SvgIO, SvgWrite};
@@ -17,6 +21,8 @@ pub struct Pages<NT: CDNum> {
     card_size: Option<(NT, NT)>,
     //init_defs: Option<&'a dyn for<'r> Fn(&'r mut (dyn SvgWrite + 'r))>,
     margin: NT,
+    fonts: Vec<FontFace>,
+    sheet: StyleSheet,
     //pc: std::marker::PhantomData<C>,
 }
 
@@ -29,6 +35,8 @@ impl<NT: CDNum> Pages<NT> {
             card_size: None,
             //            init_defs: None,
             margin: qcast(40),
+            fonts: Vec::new(),
+            sheet: StyleSheet::new(),
         }
     }
 
@@ -57,6 +65,18 @@ impl<NT: CDNum> Pages<NT> {
         self
     }
 
+    /// Embed a font at the top of every page
+    pub fn font(mut self, f: FontFace) -> Self {
+        self.fonts.push(f);
+        self
+    }
+
+    /// Write a stylesheet at the top of every page, so cards can share styles by class
+    pub fn style_sheet(mut self, s: StyleSheet) -> Self {
+        self.sheet = s;
+        self
+    }
+
     pub fn write_page<W, I, C, F, E, E2>(
         &mut self,
         svg: &mut W,
@@ -74,6 +94,10 @@ impl<NT: CDNum> Pages<NT> {
         self.write_page_with_init(svg, it, f, |_, _, _| -> Result<(), E2> { Ok(()) })
     }
 
+    /// Write one page of cards, page_init is called first.
+    ///
+    /// The page has a resource registry, so anything defined with resource::define
+    /// is written once, in a single defs element at the end of the page.
     pub fn write_page_with_init<W, I, C, F, F2, E, E2>(
         &mut self,
         svg: &mut W,
@@ -89,39 +113,109 @@ impl<NT: CDNum> Pages<NT> {
         C: Debug,
         E2: From<E>,
     {
+        self.write_cards(svg, it, f, page_init, None)
+    }
+
+    /// Write one page of cards, each drawn over the same artwork.
+    ///
+    /// "art" is called once per page with the card size, and defined as a symbol in the
+    /// page's resources, each card then places it with a use element before calling f.
+    /// The symbol's id starts "card_art", and is numbered if that is already used.
+    pub fn write_page_with_art<W, I, C, F, A, E, E2>(
+        &mut self,
+        svg: &mut W,
+        it: &mut I,
+        art: A,
+        f: F,
+    ) -> Result<usize, E2>
+    where
+        W: SvgWrite<Err = E>,
+        I: Iterator<Item = C>,
+        F: Fn(&mut dyn SvgWrite<Err = E>, NT, NT, C) -> Result<(), E2>,
+        A: Fn(&mut dyn SvgWrite<Err = E>, NT, NT) -> Result<(), E2>,
+        C: Debug,
+        E2: From<E>,
+    {
+        let (cw, ch) = self.card_dims();
+        let sym =
+            Symbol::build("card_art", |w| art(w, cw, ch))?.view_box(qcast(0), qcast(0), cw, ch);
+        self.write_cards(
+            svg,
+            it,
+            f,
+            |_, _, _| -> Result<(), E2> { Ok(()) },
+            Some(&sym),
+        )
+    }
+
+    fn card_dims(&self) -> (NT, NT) {
         let (pw, ph) = self.page_dims.unwrap_or((a4_width(), a4_height()));
         let (gw, gh) = self.grid_shape.unwrap_or((4, 4));
-        let (cw, ch) = self.card_size.unwrap_or({
+        self.card_size.unwrap_or({
             let cw = (pw - self.margin) / qcast(gw);
             let ch = (ph - self.margin) / qcast(gh);
             (cw, ch)
-        });
+        })
+    }
+
+    fn write_cards<W, I, C, F, F2, E, E2>(
+        &mut self,
+        svg: &mut W,
+        it: &mut I,
+        f: F,
+        page_init: F2,
+        art: Option<&Symbol>,
+    ) -> Result<usize, E2>
+    where
+        W: SvgWrite<Err = E>,
+        I: Iterator<Item = C>,
+        F: Fn(&mut dyn SvgWrite<Err = E>, NT, NT, C) -> Result<(), E2>,
+        F2: Fn(&mut dyn SvgWrite<Err = E>, NT, NT) -> Result<(), E2>,
+        C: Debug,
+        E2: From<E>,
+    {
+        let (pw, ph) = self.page_dims.unwrap_or((a4_width(), a4_height()));
+        let (gw, gh) = self.grid_shape.unwrap_or((4, 4));
+        let (cw, ch) = self.card_dims();
 
         let mw: NT = (pw - cw * qcast(gw)) / qcast(2);
         let mh: NT = (ph - ch * qcast(gh)) / qcast(2);
 
         let mut svg = Tag::start(svg, px(pw), px(ph))?;
+        let mut page = ResourceWrap::new(&mut svg);
+        write_fonts(&mut page, &self.fonts)?;
+        self.sheet.write(&mut page)?;
 
-        page_init(&mut svg, pw, ph)?;
+        page_init(&mut page, pw, ph)?;
+        let art = match art {
+            Some(sym) => Some((sym, define(&mut page, sym)?)),
+            None => None,
+        };
 
         let max = gw * gh;
         let mut i = 0;
 
-        while let Some(c) = it.next() {
+        for c in it {
             let x: NT = if self.flip {
                 qcast(gw - (i % gw))
             } else {
                 qcast(i % gw)
             };
             let y: NT = qcast(i / gw);
-            let mut c_loc = Tag::g().translate(mw + x * cw, mh + y * ch).wrap(&mut svg);
+            let mut c_loc = Tag::g().translate(mw + x * cw, mh + y * ch).wrap(&mut page);
+            if let Some((sym, id)) = &art {
+                sym.place_id(&mut c_loc, id, 0, 0, cw, ch)?;
+            }
             f(&mut c_loc, cw, ch, c)?;
+            c_loc.finish()?;
 
             i += 1;
             if i == max {
-                return Ok(i);
+                break;
             }
         }
+        page.finish()?;
+        svg.finish()?;
         Ok(i)
     }
 
@@ -149,6 +243,7 @@ impl<NT: CDNum> Pages<NT> {
             let w = File::create(&fname)?;
             let mut svg = SvgIO::new(w);
             let printed = self.write_page(&mut svg, it, f)?;
+            svg.flush()?;
             if printed == 0 {
                 return Ok((tot_printed, res));
             }
@@ -182,7 +277,7 @@ pub fn a4_height<T: CDNum>() -> T {
 /// ```
 pub fn page_flip<T: Clone>(v: &Vec<T>, w: usize) -> Vec<T> {
     let mut res: Vec<T> = Vec::new();
-    if v.len() == 0 {
+    if v.is_empty() {
         return res;
     }
     let blank = v[0].clone();
@@ -197,7 +292,7 @@ pub fn page_flip<T: Clone>(v: &Vec<T>, w: usize) -> Vec<T> {
         }
     }
 
-    if tmp.len() > 0 {
+    if !tmp.is_empty() {
         for _ in 0..w - tmp.len() {
             res.push(blank.clone());
         }
@@ -237,11 +332,11 @@ pub fn unite_as_pdf<P: AsRef<Path>, Q: AsRef<Path>>(v: Vec<P>, fpath: Q) -> bool
         pp.set_extension("pdf");
 
         let pps = pp.to_str().unwrap_or("cc.pdf");
-        print!("Creating : {}\n", pps);
+        println!("Creating : {}", pps);
 
         let _output = Command::new("inkscape")
             .arg(op)
-            .arg(&format!("--export-pdf={}", pps))
+            .arg(format!("--export-pdf={}", pps))
             .output()
             .expect("Could not run process");
 
@@ -249,7 +344,7 @@ pub fn unite_as_pdf<P: AsRef<Path>, Q: AsRef<Path>>(v: Vec<P>, fpath: Q) -> bool
     }
 
     pdv.push(fpath.as_ref().to_str().unwrap_or("pooyt4.pdf").to_string());
-    print!("Combining\n");
+    println!("Combining");
     Command::new("pdfunite")
         .args(pdv)
         .output()
@@ -288,4 +383,132 @@ mod page_test {
 
         assert_eq!(s, "<?xml version=\"1.0\" ?>\n<svg width=\"2480px\" height=\"3508px\" xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" >\n  <rect x=\"0\" y=\"0\" width=\"5\" height=\"5\" />\n  <g transform=\"translate(20,20) \" >\n    4050\n  </g>\n</svg>\n");
     }
+
+    #[test]
+    pub fn test_page_shares_resources() {
+        use crate::gradient::LinearGradient;
+        use crate::resource::define;
+        let mut s = String::new();
+        let mut svg = SvgFmt::new(&mut s);
+        let v = vec![NumCard(1), NumCard(2), NumCard(3)];
+        Pages::build()
+            .grid_size(3, 1)
+            .write_page(
+                &mut svg,
+                &mut v.into_iter(),
+                |mut w, _: i32, _, c: NumCard| -> std::fmt::Result {
+                    let g = LinearGradient::new("shade")
+                        .stop(0, "white")
+                        .stop(1, if c.0 == 3 { "red" } else { "black" });
+                    let p = define(&mut w, &g)?;
+                    Tag::rect(0, 0, 5, 5).fill(p).write(&mut w)
+                },
+            )
+            .unwrap();
+        assert_eq!(s.matches("<defs").count(), 1);
+        assert_eq!(s.matches("<linearGradient").count(), 2);
+        assert_eq!(s.matches("fill:url(#shade);").count(), 2);
+        assert_eq!(s.matches("fill:url(#shade_1);").count(), 1);
+        assert!(s.ends_with("</defs>\n</svg>\n"));
+    }
+
+    #[test]
+    pub fn test_art_is_hoisted() {
+        let mut s = String::new();
+        let mut svg = SvgFmt::new(&mut s);
+        let v = vec![NumCard(1), NumCard(2), NumCard(3)];
+        Pages::build()
+            .grid_size(3, 1)
+            .card_size(10, 10)
+            .write_page_with_art(
+                &mut svg,
+                &mut v.into_iter(),
+                |mut w, cw, ch| Tag::rect(0, 0, cw, ch).stroke("gold").write(&mut w),
+                draw_card,
+            )
+            .unwrap();
+        assert_eq!(s.matches("<symbol").count(), 1);
+        assert_eq!(s.matches("<rect").count(), 1);
+        assert_eq!(
+            s.matches(r##"<use href="#card_art" x="0" y="0" width="10" height="10" />"##)
+                .count(),
+            3
+        );
+        assert!(s.contains(r#"<symbol id="card_art" viewBox="0 0 10 10" >"#));
+    }
+
+    #[test]
+    pub fn test_art_ids_unique_across_pages() {
+        let mut s = String::new();
+        let mut svg = SvgFmt::new(&mut s);
+        let v = vec![NumCard(1), NumCard(2), NumCard(3)];
+        let mut it = v.into_iter();
+        let mut pages = Pages::build().grid_size(2, 1).card_size(10, 10);
+        let art = |mut w: &mut dyn SvgWrite<Err = std::fmt::Error>, cw, ch| {
+            Tag::rect(0, 0, cw, ch).write(&mut w)
+        };
+        let own = |mut w: &mut dyn SvgWrite<Err = std::fmt::Error>, _, _, _| {
+            Symbol::new("card_art")
+                .child(Tag::circle(1, 1, 1))
+                .place(&mut w, 0, 0, 5, 5)?;
+            Ok(())
+        };
+        pages
+            .write_page_with_art(&mut svg, &mut it, art, own)
+            .unwrap();
+        pages
+            .write_page_with_art(&mut svg, &mut it, art, own)
+            .unwrap();
+        let art_use = |id: &str| {
+            format!(
+                r##"<use href="#{}" x="0" y="0" width="10" height="10" />"##,
+                id
+            )
+        };
+        let own_use = |id: &str| {
+            format!(
+                r##"<use href="#{}" x="0" y="0" width="5" height="5" />"##,
+                id
+            )
+        };
+        assert!(s.contains(r#"<symbol id="card_art" viewBox="0 0 10 10" >"#));
+        assert!(s.contains(r#"<symbol id="card_art_2" viewBox="0 0 10 10" >"#));
+        assert_eq!(s.matches(&art_use("card_art")).count(), 2);
+        assert_eq!(s.matches(&art_use("card_art_2")).count(), 1);
+        assert_eq!(s.matches(&own_use("card_art_1")).count(), 2);
+        assert_eq!(s.matches(&own_use("card_art_3")).count(), 1);
+        assert_eq!(s.matches("<symbol").count(), 4);
+        assert_eq!(s.matches("<rect").count(), 2);
+        assert_eq!(s.matches("<circle").count(), 2);
+    }
+
+    #[test]
+    pub fn test_fonts_at_page_top() {
+        let mut s = String::new();
+        let mut svg = SvgFmt::new(&mut s);
+        let face = crate::font::FontFace::from_bytes("Runes", b"wOF2").unwrap();
+        Pages::build()
+            .font(face)
+            .write_page(&mut svg, &mut vec![NumCard(1)].into_iter(), draw_card)
+            .unwrap();
+        let style = s
+            .find("<style ><![CDATA[@font-face {font-family:\"Runes\"")
+            .unwrap();
+        assert!(style < s.find("<g").unwrap());
+    }
+
+    #[test]
+    pub fn test_sheet_at_page_top() {
+        let mut s = String::new();
+        let mut svg = SvgFmt::new(&mut s);
+        let sheet = StyleSheet::new().rule(crate::style::Rule::for_class("num").fill("red"));
+        Pages::build()
+            .style_sheet(sheet)
+            .write_page(&mut svg, &mut vec![NumCard(1)].into_iter(), draw_card)
+            .unwrap();
+        let style = s
+            .find("<style ><![CDATA[.num {fill:red;}]]></style>")
+            .unwrap();
+        assert!(style < s.find("<g").unwrap());
+    }
 }
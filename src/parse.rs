@@ -0,0 +1,315 @@
+//! Reads existing svg (or any xml) into an SvgDoc, so artwork made elsewhere can be used
+//! as a template, modified and written out again through any SvgWrite.
+//!
+//! Style arguments are split back into style items, and transform arguments into
+//! transform items, so they can be added to with the SvgArg methods.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::parse::parse_str;
+//! let doc = parse_str(r#"<svg><rect id="r" style="fill:red" transform="translate(3 4)"/></svg>"#).unwrap();
+//! let r = doc.find_by_id("r").unwrap().clone().stroke("black");
+//! assert_eq!(
+//!     r.to_string(),
+//!     "<rect id=\"r\" style=\"fill:red;stroke:black;\" transform=\"translate(3,4) \" />\n"
+//! );
+//!
+//! let e = parse_str("<svg>\n  <g>\n</svg>").unwrap_err();
+//! assert_eq!((e.line, e.col), (3, 1));
+//! ```
+
+use crate::args::{Args, SvgArg};
+use crate::doc::{Element, SvgDoc};
+use crate::err::{DocError, ParseError};
+use crate::escape::unescape;
+use std::path::Path;
+
+pub(crate) enum Token {
+    Open(Element),
+    Empty(Element),
+    Close(String),
+    Text(String),
+    Raw(String),
+}
+
+impl ParseError {
+    /// build an error for the byte offset "pos" of "s"
+    pub fn at(s: &str, pos: usize, msg: &str) -> Self {
+        let before = &s[..pos];
+        let line = before.matches('\n').count() + 1;
+        let col = match before.rfind('\n') {
+            Some(n) => before[n + 1..].chars().count() + 1,
+            None => before.chars().count() + 1,
+        };
+        ParseError {
+            line,
+            col,
+            msg: msg.to_string(),
+        }
+    }
+}
+
+/// Read a whole document, every opened tag must be closed.
+pub fn parse_str(s: &str) -> Result<SvgDoc, ParseError> {
+    let mut doc = SvgDoc::new();
+    let mut opened = Vec::new();
+    for (pos, t) in tokens(s)? {
+        match &t {
+            Token::Open(_) => opened.push(pos),
+            Token::Close(_) => {
+                opened.pop();
+            }
+            _ => {}
+        }
+        if let Err(e) = doc.add_token(t) {
+            return Err(ParseError::at(s, pos, &e.to_string()));
+        }
+    }
+    match opened.pop() {
+        Some(pos) => Err(ParseError::at(s, pos, "Tag is never closed")),
+        None => Ok(doc),
+    }
+}
+
+pub fn parse_file<P: AsRef<Path>>(p: P) -> Result<SvgDoc, DocError> {
+    let s = std::fs::read_to_string(p)?;
+    Ok(parse_str(&s)?)
+}
+
+impl std::str::FromStr for SvgDoc {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        parse_str(s)
+    }
+}
+
+/// split s on sep, ignoring anything inside quotes or brackets
+fn split_outer(s: &str, sep: char) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if q == c => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, _) if c == sep && depth == 0 => {
+                res.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    res.push(&s[start..]);
+    res
+}
+
+impl Args {
+    /// add an argument as it was read from xml, "style" is split into style items
+    /// and "transform" into transform items, anything else is a plain argument.
+    ///
+    /// ```
+    /// use mksvg::args::{Args,SvgArg};
+    /// let a = Args::new().xml_arg("transform","rotate(3) translate(1 2)").xml_arg("style","fill:red");
+    /// assert_eq!(a, Args::new().transform("rotate", &[3]).translate(1, 2).fill("red"));
+    /// ```
+    pub fn xml_arg(self, k: &str, v: &str) -> Self {
+        match k {
+            "style" => split_outer(v, ';').into_iter().fold(self, |a, item| {
+                let mut kv = item.splitn(2, ':');
+                match (kv.next(), kv.next()) {
+                    (Some(k), Some(v)) if !k.trim().is_empty() => a.style(k.trim(), v.trim()),
+                    _ => a,
+                }
+            }),
+            "transform" => {
+                let mut a = self;
+                let mut rest = v;
+                while let (Some(o), Some(c)) = (rest.find('('), rest.find(')')) {
+                    if c < o {
+                        break;
+                    }
+                    let k = rest[..o].trim_matches(|c: char| c == ',' || c.is_whitespace());
+                    let vals: Vec<&str> = rest[o + 1..c]
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|s| !s.is_empty())
+                        .collect();
+                    a = a.transform(k, &vals);
+                    rest = &rest[c + 1..];
+                }
+                a
+            }
+            _ => self.arg(k, v),
+        }
+    }
+}
+
+pub(crate) fn tokens(s: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut res = Vec::new();
+    let mut pos = 0;
+    while pos < s.len() {
+        let rest = &s[pos..];
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            res.push((pos, Token::Text(unescape(&rest[..end]).into_owned())));
+            pos += end;
+            continue;
+        }
+        let close = if rest.starts_with("<!--") {
+            "-->"
+        } else if rest.starts_with("<![CDATA[") {
+            "]]>"
+        } else if rest.starts_with("<?") {
+            "?>"
+        } else if rest.starts_with("<!") {
+            if rest.contains('[') && rest.find('[') < rest.find('>') {
+                "]>"
+            } else {
+                ">"
+            }
+        } else {
+            ""
+        };
+        if !close.is_empty() {
+            let end = match rest.find(close) {
+                Some(n) => n + close.len(),
+                None => return Err(ParseError::at(s, pos, "Unclosed markup")),
+            };
+            res.push((pos, Token::Raw(rest[..end].to_string())));
+            pos += end;
+            continue;
+        }
+        if let Some(r) = rest.strip_prefix("</") {
+            let end = match r.find('>') {
+                Some(n) => n,
+                None => return Err(ParseError::at(s, pos, "Unclosed end tag")),
+            };
+            res.push((pos, Token::Close(r[..end].trim().to_string())));
+            pos += end + 3;
+            continue;
+        }
+        let (t, len) = open_tag(s, pos)?;
+        res.push((pos, t));
+        pos += len;
+    }
+    Ok(res)
+}
+
+/// reads the start tag at "start", returning it and its length in bytes
+fn open_tag(s: &str, start: usize) -> Result<(Token, usize), ParseError> {
+    let is_name_end = |c: char| c.is_whitespace() || c == '/' || c == '>';
+    let mut pos = start + 1;
+    let end = s[pos..].find(is_name_end).unwrap_or(s.len() - pos);
+    if end == 0 {
+        return Err(ParseError::at(s, pos, "Missing tag name"));
+    }
+    let mut e = Element::new(&s[pos..pos + end]);
+    pos += end;
+    loop {
+        let r = &s[pos..];
+        pos += r.len() - r.trim_start().len();
+        let r = &s[pos..];
+        if r.starts_with("/>") {
+            return Ok((Token::Empty(e), pos + 2 - start));
+        }
+        if r.starts_with('>') {
+            return Ok((Token::Open(e), pos + 1 - start));
+        }
+        let kend = r
+            .find(|c: char| c == '=' || is_name_end(c))
+            .unwrap_or(r.len());
+        if kend == 0 {
+            return Err(ParseError::at(s, pos, "Unclosed start tag"));
+        }
+        let k = &r[..kend];
+        let r2 = r[kend..].trim_start();
+        let r2 = match r2.strip_prefix('=') {
+            Some(r2) => r2.trim_start(),
+            None => return Err(ParseError::at(s, pos, "Expected '=' after argument name")),
+        };
+        let vpos = pos + r.len() - r2.len();
+        let q = match r2.chars().next() {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => return Err(ParseError::at(s, vpos, "Expected quote")),
+        };
+        let vend = match r2[1..].find(q) {
+            Some(n) => n + 1,
+            None => return Err(ParseError::at(s, vpos, "Unclosed quote")),
+        };
+        e.args = e.args.xml_arg(k, &unescape(&r2[1..vend]));
+        pos = vpos + vend + 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::doc::Node;
+    use crate::tag::Tag;
+    use crate::write::SvgFmt;
+
+    #[test]
+    pub fn test_parse_inkscape_like() {
+        let src = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- Created with Inkscape -->
+<svg width="210mm" height="297mm" xmlns="http://www.w3.org/2000/svg">
+  <g id="layer1" style="display:inline;opacity:0.5" transform="matrix(1,0,0,1,-10,5.5)">
+    <rect id="frame" x="0" y="0" width="10" height='20' style="fill:#ff0000;font-family:'A;B'" />
+    <text x="3" y="4">Hi &amp; bye</text>
+  </g>
+</svg>"#;
+        let doc = parse_str(src).unwrap();
+        assert_eq!(doc.nodes().len(), 3);
+        let g = doc.find_by_id("layer1").unwrap();
+        assert_eq!(
+            g.args,
+            Args::new()
+                .id("layer1")
+                .style("display", "inline")
+                .style("opacity", "0.5")
+                .transform("matrix", &["1", "0", "0", "1", "-10", "5.5"])
+        );
+        let t = &doc.find_by_name("text")[0];
+        assert_eq!(t.children, vec![Node::Text("Hi & bye".to_string())]);
+        let r = doc.find_by_id("frame").unwrap();
+        assert_eq!(r.get("height"), Some("20"));
+    }
+
+    #[test]
+    pub fn test_parse_round_trip() {
+        let mut s = String::new();
+        {
+            let mut w = SvgFmt::new(&mut s);
+            let mut svg = Tag::start(&mut w, 20, 20).unwrap();
+            let mut g = Tag::g().translate(3, 4).wrap(&mut svg);
+            Tag::rect(0, 0, 5, 5).fill("red").write(&mut g).unwrap();
+        }
+        let doc = parse_str(&s).unwrap();
+        assert_eq!(doc.to_string(), s);
+    }
+
+    #[test]
+    pub fn test_mixed_content_round_trip() {
+        let src = "<svg >\n  <text x=\"0\" y=\"5\" >Hello <tspan >world</tspan> again</text>\n  \
+                   <text >Hello<tspan >world</tspan></text>\n  <text > <tspan >a</tspan> <tspan >b</tspan></text>\n</svg>\n";
+        let doc = parse_str(src).unwrap();
+        let t = doc.find_by_name("text")[0];
+        assert_eq!(t.children[0], Node::Text("Hello ".to_string()));
+        assert_eq!(t.children[2], Node::Text(" again".to_string()));
+        assert_eq!(doc.to_string(), src);
+        assert_eq!(parse_str(&doc.to_string()).unwrap().nodes(), doc.nodes());
+    }
+
+    #[test]
+    pub fn test_parse_errors() {
+        let e = parse_str("<svg>\n  <rect x=3/>\n</svg>").unwrap_err();
+        assert_eq!((e.line, e.col), (2, 11));
+        let e = parse_str("<svg>\n<g></a>\n</svg>").unwrap_err();
+        assert_eq!((e.line, e.col), (2, 4));
+        let e = parse_str("<svg><!-- never closed").unwrap_err();
+        assert_eq!((e.line, e.col), (1, 6));
+    }
+}
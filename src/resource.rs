@@ -0,0 +1,383 @@
+//! A registry for reusable definitions (gradients, clip paths, symbols, filters ...)
+//!
+//! Anything that implements Resource can be defined with "define". If the writer has a
+//! Resources registry (as every page written by Pages does), the definition is given a
+//! unique id, identical definitions share one id, and they are all written in a single
+//! defs element when the page closes. Otherwise the definition is written in place,
+//! inside its own defs element, with its id numbered if it has been used before.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::gradient::LinearGradient;
+//! use mksvg::resource::{define, ResourceWrap};
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     let mut page = ResourceWrap::new(&mut svg);
+//!     for _ in 0..2 {
+//!         let g = LinearGradient::new("gold").stop(0, "gold").stop(1, "white");
+//!         let p = define(&mut page, &g).unwrap();
+//!         Tag::rect(0, 0, 5, 5).fill(p).write(&mut page).unwrap();
+//!     }
+//!     let other = LinearGradient::new("gold").stop(0, "gold");
+//!     let p = define(&mut page, &other).unwrap();
+//!     Tag::rect(0, 0, 5, 5).fill(p).write(&mut page).unwrap();
+//!     page.finish().unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r#"<rect x="0" y="0" width="5" height="5" style="fill:url(#gold);" />
+//! <rect x="0" y="0" width="5" height="5" style="fill:url(#gold);" />
+//! <rect x="0" y="0" width="5" height="5" style="fill:url(#gold_1);" />
+//! <defs >
+//!   <linearGradient id="gold" >
+//!     <stop offset="0" style="stop-color:gold;" />
+//!     <stop offset="1" style="stop-color:white;" />
+//!   </linearGradient>
+//!   <linearGradient id="gold_1" >
+//!     <stop offset="0" style="stop-color:gold;" />
+//!   </linearGradient>
+//! </defs>
+//! "#
+//! );
+//! ```
+
+use crate::format::Format;
+use crate::ns::Ns;
+use crate::tag::Tag;
+use crate::write::SvgWrite;
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::fmt;
+use std::fmt::Display;
+
+/// A reference to an element by id. Displays as "url(#id)" so it can be used
+/// directly as a fill or stroke, use href for "#id".
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IdRef(String);
+
+impl IdRef {
+    pub fn new<T: Display>(id: T) -> Self {
+        IdRef(id.to_string())
+    }
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+    pub fn href(&self) -> String {
+        format!("#{}", self.0)
+    }
+}
+
+impl Display for IdRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "url(#{})", self.0)
+    }
+}
+
+/// The ids given out for one document, so generated ids do not clash
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Ids {
+    used: HashSet<String>,
+}
+
+impl Ids {
+    pub fn new() -> Self {
+        Ids::default()
+    }
+
+    /// prefix if it is free, otherwise prefix_1, prefix_2 ...
+    pub fn claim(&mut self, prefix: &str) -> String {
+        let mut id = prefix.to_string();
+        let mut n = 0;
+        while self.used.contains(&id) {
+            n += 1;
+            id = format!("{}_{}", prefix, n);
+        }
+        self.used.insert(id.clone());
+        id
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.used.contains(id)
+    }
+}
+
+pub trait Resource {
+    /// The id to use, if it is free. Later different definitions get a numbered suffix.
+    fn id_prefix(&self) -> &str;
+    /// Write the definition with the given id
+    fn write_def<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E>;
+
+    /// Write the definition now, this should be inside a defs element.
+    /// The id is claimed from the writer's Ids as new_id, so it may be numbered.
+    fn write<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<IdRef, E> {
+        let id = new_id(w, self.id_prefix());
+        self.write_def(&id, w)?;
+        Ok(IdRef::new(id))
+    }
+
+    /// As write, inside its own defs element
+    fn write_defs<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<IdRef, E> {
+        let mut d = Tag::defs().wrap(w);
+        let id = self.write(&mut d)?;
+        d.finish()?;
+        Ok(id)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Rec {
+    Write(String),
+    Depth(i8),
+}
+
+/// Keeps everything written to it, so it can be written again later
+#[derive(Clone, Debug, PartialEq, Default)]
+struct Recorder {
+    items: Vec<Rec>,
+    f: Format,
+    d: i8,
+}
+
+impl Recorder {
+    fn replay<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
+        for r in &self.items {
+            match r {
+                Rec::Write(s) => w.write(s)?,
+                Rec::Depth(n) => w.inc_depth(*n),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SvgWrite for Recorder {
+    type Err = Infallible;
+    fn write(&mut self, s: &str) -> Result<(), Infallible> {
+        self.items.push(Rec::Write(s.to_string()));
+        Ok(())
+    }
+    fn inc_depth(&mut self, n: i8) {
+        self.d += n;
+        self.items.push(Rec::Depth(n));
+    }
+    fn depth(&self) -> i8 {
+        self.d
+    }
+    fn format(&self) -> Format {
+        self.f.clone()
+    }
+}
+
+/// d is the depth the recording will be replayed at
+fn record<R: Resource>(r: &R, id: &str, f: &Format, d: i8) -> Recorder {
+    let mut rec = Recorder {
+        items: Vec::new(),
+        f: f.clone(),
+        d,
+    };
+    match r.write_def(id, &mut rec) {
+        Ok(()) => rec,
+        Err(e) => match e {},
+    }
+}
+
+struct Entry {
+    key: Recorder,
+    id: String,
+    body: Recorder,
+}
+
+/// The registry of definitions for one document
+#[derive(Default)]
+pub struct Resources {
+    entries: Vec<Entry>,
+    ids: Ids,
+    f: Format,
+    /// the depth of the definitions inside the defs element
+    d: i8,
+}
+
+impl Resources {
+    /// f is the format of the writer the definitions will be written to
+    pub fn new(f: Format) -> Self {
+        Resources {
+            entries: Vec::new(),
+            ids: Ids::new(),
+            f,
+            d: 1,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Add a definition, returning the id of an identical earlier definition if there is one
+    pub fn add<R: Resource>(&mut self, r: &R) -> IdRef {
+        let key = record(r, "", &self.f, self.d);
+        if let Some(e) = self.entries.iter().find(|e| e.key == key) {
+            return IdRef::new(&e.id);
+        }
+        let id = self.ids.claim(r.id_prefix());
+        let body = record(r, &id, &self.f, self.d);
+        self.entries.push(Entry {
+            key,
+            id: id.clone(),
+            body,
+        });
+        IdRef::new(id)
+    }
+
+    /// Write every definition inside one defs element, nothing is written if empty
+    pub fn write_defs<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut d = Tag::defs().wrap(w);
+        d.force()?;
+        for e in &self.entries {
+            e.body.replay(&mut d)?;
+        }
+        d.finish()
+    }
+}
+
+/// A new id from the writer's Ids, or the prefix as it is if the writer has none
+pub fn new_id<W: SvgWrite<Err = E>, E>(w: &mut W, prefix: &str) -> String {
+    match w.ids() {
+        Some(ids) => ids.claim(prefix),
+        None => prefix.to_string(),
+    }
+}
+
+/// Define r using the writer's registry if it has one,
+/// otherwise write it straight away with Resource::write_defs.
+///
+/// Without a registry the id is numbered using the writer's Ids, so each call gets
+/// a new id. A writer with neither (see SvgWrite::ids) uses the prefix as it is,
+/// so defining twice there repeats the id.
+pub fn define<R: Resource, W: SvgWrite<Err = E>, E>(w: &mut W, r: &R) -> Result<IdRef, E> {
+    if let Some(res) = w.resources() {
+        return Ok(res.add(r));
+    }
+    r.write_defs(w)
+}
+
+/// A writer with a Resources registry, the definitions are written on finish
+/// or drop, after everything else. Ids already used on the parent writer are
+/// avoided, and the ids used here are passed back to it on close.
+pub struct ResourceWrap<'a, E> {
+    w: &'a mut dyn SvgWrite<Err = E>,
+    res: Resources,
+    done: bool,
+}
+
+impl<'a, E> ResourceWrap<'a, E> {
+    pub fn new(w: &'a mut dyn SvgWrite<Err = E>) -> Self {
+        let mut res = Resources::new(w.format());
+        res.d = w.depth() + 1;
+        if let Some(ids) = w.ids() {
+            res.ids = ids.clone();
+        }
+        ResourceWrap {
+            w,
+            res,
+            done: false,
+        }
+    }
+
+    /// write the definitions, returning any error
+    pub fn finish(mut self) -> Result<(), E> {
+        self.close()
+    }
+
+    fn close(&mut self) -> Result<(), E> {
+        if self.done {
+            return Ok(());
+        }
+        self.done = true;
+        if let Some(ids) = self.w.ids() {
+            *ids = self.res.ids.clone();
+        }
+        self.res.write_defs(&mut self.w)
+    }
+}
+
+impl<'a, E> SvgWrite for ResourceWrap<'a, E> {
+    type Err = E;
+    fn write(&mut self, s: &str) -> Result<(), E> {
+        self.w.write(s)
+    }
+    fn inc_depth(&mut self, n: i8) {
+        self.w.inc_depth(n)
+    }
+    fn depth(&self) -> i8 {
+        self.w.depth()
+    }
+    fn format(&self) -> Format {
+        self.w.format()
+    }
+    fn defer_err(&mut self, e: E) {
+        self.w.defer_err(e)
+    }
+    fn flush(&mut self) -> Result<(), E> {
+        self.w.flush()
+    }
+    fn resources(&mut self) -> Option<&mut Resources> {
+        Some(&mut self.res)
+    }
+    fn ids(&mut self) -> Option<&mut Ids> {
+        Some(&mut self.res.ids)
+    }
+    fn ns_declared(&self, ns: Ns) -> bool {
+        self.w.ns_declared(ns)
+    }
+}
+
+impl<'a, E> Drop for ResourceWrap<'a, E> {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            self.w.defer_err(e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gradient::LinearGradient;
+    use crate::write::SvgFmt;
+
+    #[test]
+    pub fn test_define_without_registry_numbers_ids() {
+        let mut s = String::new();
+        let mut svg = SvgFmt::new(&mut s);
+        let a = LinearGradient::new("g").stop(0, "red");
+        let b = LinearGradient::new("g").stop(0, "blue");
+        assert_eq!(define(&mut svg, &a).unwrap().id(), "g");
+        assert_eq!(define(&mut svg, &b).unwrap().id(), "g_1");
+        {
+            let mut page = ResourceWrap::new(&mut svg);
+            assert_eq!(define(&mut page, &a).unwrap().id(), "g_2");
+            let mut g = Tag::g().wrap(&mut page);
+            assert_eq!(define(&mut g, &b).unwrap().id(), "g_3");
+        }
+        assert_eq!(define(&mut svg, &a).unwrap().id(), "g_4");
+    }
+
+    #[test]
+    pub fn test_write_defs_claims_ids() {
+        let mut s = String::new();
+        {
+            let mut svg = SvgFmt::new(&mut s);
+            let a = LinearGradient::new("g").stop(0, "red");
+            let b = LinearGradient::new("g").stop(0, "blue");
+            assert_eq!(a.write_defs(&mut svg).unwrap().id(), "g");
+            assert_eq!(define(&mut svg, &b).unwrap().id(), "g_1");
+            let mut defs = Tag::defs().wrap(&mut svg);
+            assert_eq!(a.write(&mut defs).unwrap().id(), "g_2");
+        }
+        assert_eq!(s.matches(r#"<linearGradient id="g" >"#).count(), 1);
+    }
+}
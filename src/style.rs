@@ -0,0 +1,181 @@
+//! Stylesheets, so elements can share styles by class instead of each carrying its own.
+//!
+//! A Rule takes the same style methods as any SvgArg, and a StyleSheet is written as a
+//! style element, with the css in a CDATA section so it needs no escaping.
+//! Pages::style_sheet writes one at the top of every page.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::style::{Rule, StyleSheet};
+//! let sheet = StyleSheet::new()
+//!     .rule(Rule::for_class("cost").fill("gold").font_size(5))
+//!     .rule(Rule::new("g.card > rect").stroke("black").stroke_width(2));
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     sheet.write(&mut svg).unwrap();
+//!     Tag::circle(0, 0, 3).class("cost").write(&mut svg).unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r#"<style ><![CDATA[.cost {fill:gold;font-size:5;}
+//! g.card > rect {stroke:black;stroke-width:2;}]]></style>
+//! <circle cx="0" cy="0" r="3" class="cost" />
+//! "#
+//! );
+//! ```
+
+use crate::args::{is_presentation, Args, SvgArg};
+use crate::doc::Node;
+use crate::tag::Tag;
+use crate::write::SvgWrite;
+use std::fmt;
+use std::fmt::Display;
+
+/// A css rule, a selector and its properties
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    selector: String,
+    props: Args,
+}
+
+impl Rule {
+    pub fn new<T: Display>(selector: T) -> Self {
+        Rule {
+            selector: selector.to_string(),
+            props: Args::new(),
+        }
+    }
+
+    /// A rule for every element with the class, as set by SvgArg::class
+    pub fn for_class<T: Display>(name: T) -> Self {
+        Rule::new(format!(".{}", name))
+    }
+
+    pub fn selector(&self) -> &str {
+        &self.selector
+    }
+}
+
+/// Styles and presentation arguments become properties, other arguments such as
+/// x or id cannot be set by css and are dropped.
+/// Transforms are ignored as css transforms need units.
+impl SvgArg for Rule {
+    fn arg<T: Display>(mut self, k: &str, v: T) -> Self {
+        if is_presentation(k) {
+            self.props = self.props.style(k, v);
+        }
+        self
+    }
+    fn style<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.props = self.props.style(k, v);
+        self
+    }
+    fn transform<T: Display>(self, _k: &str, _args: &[T]) -> Self {
+        self
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {{", self.selector)?;
+        for (k, v) in self.props.styles() {
+            write!(f, "{}:{};", k, v)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct StyleSheet {
+    rules: Vec<Rule>,
+}
+
+impl StyleSheet {
+    pub fn new() -> Self {
+        StyleSheet { rules: Vec::new() }
+    }
+
+    pub fn rule(mut self, r: Rule) -> Self {
+        self.rules.push(r);
+        self
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Write the sheet as a style element, nothing is written if it is empty
+    pub fn write<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        write_css(w, &self.to_string())
+    }
+}
+
+/// Write a style element with the css in a CDATA section, shared with font::write_fonts
+pub(crate) fn write_css<W: SvgWrite<Err = E>, E>(w: &mut W, css: &str) -> Result<(), E> {
+    let css = css.replace("]]>", "]]]]><![CDATA[>");
+    Tag::style_tag()
+        .child(Node::Raw(format!("<![CDATA[{}]]>", css)))
+        .write(w)
+}
+
+/// s as a css string in quotes q, escaping any q or backslash inside it
+pub(crate) fn css_string(s: &str, q: char) -> String {
+    let mut res = q.to_string();
+    for c in s.chars() {
+        if c == q || c == '\\' {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res.push(q);
+    res
+}
+
+/// The css text, one rule per line
+impl Display for StyleSheet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, r) in self.rules.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", r)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parse::parse_str;
+
+    #[test]
+    pub fn test_sheet_round_trip() {
+        let sheet = StyleSheet::new().rule(
+            Rule::new("a < b & c")
+                .fill("red")
+                .x(3)
+                .id("no")
+                .arg("opacity", 0.5),
+        );
+        let mut s = String::new();
+        sheet.write(&mut crate::write::SvgFmt::new(&mut s)).unwrap();
+        let doc = parse_str(&s).unwrap();
+        let st = doc.find_by_name("style")[0];
+        assert_eq!(
+            st.children,
+            vec![Node::Raw(
+                "<![CDATA[a < b & c {fill:red;opacity:0.5;}]]>".to_string()
+            )]
+        );
+        assert_eq!(StyleSheet::new().to_string(), "");
+    }
+}
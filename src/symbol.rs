@@ -0,0 +1,215 @@
+//! Symbols, artwork defined once and placed many times with use.
+//!
+//! A Symbol can be built from a closure, in the same way as drawing a card. It is a
+//! Resource, so place defines it in the page's defs the first time, and only writes a
+//! use element after that. Pages::write_page_with_art does this for artwork shared by
+//! every card.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::symbol::Symbol;
+//! use mksvg::resource::ResourceWrap;
+//! let mut s = String::new();
+//! {
+//!     let mut svg = SvgFmt::new(&mut s);
+//!     let mut page = ResourceWrap::new(&mut svg);
+//!     let frame = Symbol::new("frame")
+//!         .view_box(0, 0, 10, 10)
+//!         .child(Tag::rect(0, 0, 10, 10).stroke("black"));
+//!     frame.place(&mut page, 0, 0, 10, 10).unwrap();
+//!     frame.place(&mut page, 20, 0, 10, 10).unwrap();
+//!     page.finish().unwrap();
+//! }
+//! assert_eq!(
+//!     s,
+//!     r##"<use href="#frame" x="0" y="0" width="10" height="10" />
+//! <use href="#frame" x="20" y="0" width="10" height="10" />
+//! <defs >
+//!   <symbol id="frame" viewBox="0 0 10 10" >
+//!     <rect x="0" y="0" width="10" height="10" style="stroke:black;" />
+//!   </symbol>
+//! </defs>
+//! "##
+//! );
+//! ```
+
+use crate::args::{Args, SvgArg};
+use crate::doc::Node;
+use crate::parse::parse_str;
+use crate::resource::{define, IdRef, Resource};
+use crate::tag::Tag;
+use crate::write::{SvgFmt, SvgWrite};
+use std::fmt;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+/// Keeps everything written to it as text, it never fails
+struct Capture<E> {
+    s: String,
+    e: PhantomData<fn() -> E>,
+}
+
+impl<E> SvgWrite for Capture<E> {
+    type Err = E;
+    fn write(&mut self, s: &str) -> Result<(), E> {
+        self.s.push_str(s);
+        self.s.push('\n');
+        Ok(())
+    }
+    fn inc_depth(&mut self, _n: i8) {}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symbol {
+    id: String,
+    args: Args,
+    trans: Args,
+    children: Vec<Node>,
+}
+
+impl Symbol {
+    pub fn new<T: Display>(id: T) -> Self {
+        Symbol {
+            id: id.to_string(),
+            args: Args::new(),
+            trans: Args::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Build a symbol from everything f writes
+    pub fn build<T, F, E, E2>(id: T, f: F) -> Result<Self, E2>
+    where
+        T: Display,
+        F: FnOnce(&mut dyn SvgWrite<Err = E>) -> Result<(), E2>,
+    {
+        let mut c = Capture {
+            s: String::new(),
+            e: PhantomData,
+        };
+        f(&mut c)?;
+        let nodes = match parse_str(&c.s) {
+            Ok(mut d) => std::mem::take(d.nodes_mut()),
+            Err(_) => vec![Node::Raw(c.s)],
+        };
+        let mut res = Symbol::new(id);
+        res.children = nodes;
+        Ok(res)
+    }
+
+    pub fn child<N: Into<Node>>(mut self, n: N) -> Self {
+        self.children.push(n.into());
+        self
+    }
+
+    /// Define the symbol if needed, and write a use element for it, with any transforms.
+    /// Without a resource registry the symbol is defined again each time.
+    pub fn place<W: SvgWrite<Err = E>, E, P: Display, S: Display>(
+        &self,
+        w: &mut W,
+        x: P,
+        y: P,
+        width: S,
+        height: S,
+    ) -> Result<IdRef, E> {
+        let id = define(w, self)?;
+        self.place_id(w, &id, x, y, width, height)?;
+        Ok(id)
+    }
+
+    /// Write a use element for this symbol, already defined as id
+    pub fn place_id<W: SvgWrite<Err = E>, E, P: Display, S: Display>(
+        &self,
+        w: &mut W,
+        id: &IdRef,
+        x: P,
+        y: P,
+        width: S,
+        height: S,
+    ) -> Result<(), E> {
+        Tag::use_tag(id.href())
+            .xy(x, y)
+            .wh(width, height)
+            .merge(&self.trans)
+            .write(w)
+    }
+}
+
+impl Resource for Symbol {
+    fn id_prefix(&self) -> &str {
+        &self.id
+    }
+    fn write_def<W: SvgWrite<Err = E>, E>(&self, id: &str, w: &mut W) -> Result<(), E> {
+        let mut t = Tag::symbol(id).merge(&self.args);
+        for n in &self.children {
+            t = t.child(n.clone());
+        }
+        t.write(w)
+    }
+}
+
+/// Arguments such as viewBox and preserveAspectRatio go on the symbol element,
+/// transforms go on each use element written by place
+impl SvgArg for Symbol {
+    fn arg<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.arg(k, v);
+        self
+    }
+    fn style<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.style(k, v);
+        self
+    }
+    fn transform<T: Display>(mut self, k: &str, args: &[T]) -> Self {
+        self.trans = self.trans.transform(k, args);
+        self
+    }
+}
+
+/// Displays as the symbol element, use the IdRef from place or define to refer to it
+impl Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_def(&self.id, &mut SvgFmt::new(f))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resource::ResourceWrap;
+
+    #[test]
+    pub fn test_transform_on_use() {
+        let mut s = String::new();
+        {
+            let mut svg = SvgFmt::new(&mut s);
+            let mut page = ResourceWrap::new(&mut svg);
+            let dot = Symbol::new("dot").child(Tag::circle(5, 5, 5));
+            dot.place(&mut page, 0, 0, 10, 10).unwrap();
+            dot.clone()
+                .rotate(90, 5, 5)
+                .place(&mut page, 0, 0, 10, 10)
+                .unwrap();
+            page.finish().unwrap();
+        }
+        assert_eq!(s.matches("<symbol").count(), 1);
+        assert!(s.contains(r#"<symbol id="dot" >"#));
+        assert!(s.contains(
+            r##"<use href="#dot" x="0" y="0" width="10" height="10" transform="rotate(90,5,5) " />"##
+        ));
+    }
+
+    #[test]
+    pub fn test_place_uses_defined_id() {
+        let mut s = String::new();
+        {
+            let mut svg = SvgFmt::new(&mut s);
+            let mut page = ResourceWrap::new(&mut svg);
+            let dot = Symbol::new("dot").child(Tag::circle(5, 5, 5));
+            let sq = Symbol::new("dot").child(Tag::rect(0, 0, 10, 10));
+            assert_eq!(dot.place(&mut page, 0, 0, 10, 10).unwrap().id(), "dot");
+            assert_eq!(sq.place(&mut page, 0, 0, 10, 10).unwrap().id(), "dot_1");
+        }
+        assert!(s.contains(r##"<use href="#dot_1" "##));
+        assert!(s.contains(r#"<symbol id="dot_1" >"#));
+    }
+}
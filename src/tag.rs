@@ -1,12 +1,50 @@
-use crate::args::{Args, SvgArg};
-use crate::write::{SvgWrite, TransWrap};
+use crate::args::{Args, StyleMode, SvgArg};
+use crate::doc::{write_tree, Element, Node};
+use crate::ns::{self, Ns};
+use crate::path::PathD;
+use crate::resource::new_id;
+use crate::write::{SvgFmt, SvgWrite, TransWrap};
+use std::borrow::Cow;
 use std::fmt;
 use std::fmt::Display;
 
+/// A single element, with any children it owns.
+///
+/// Tags can be built once, stored and cloned, then written any number of times.
+///
+/// ```
+/// use mksvg::*;
+/// fn badge(cost: u32) -> Tag {
+///     Tag::g()
+///         .class("badge")
+///         .child(Tag::circle(0, 0, 5).fill("gold"))
+///         .child(Tag::text_tag(0, 2).text(cost))
+/// }
+/// let b = badge(3);
+/// let mut s = String::new();
+/// {
+///     let mut svg = SvgFmt::new(&mut s);
+///     b.clone().translate(10, 10).write(&mut svg).unwrap();
+///     b.translate(20, 10).write(&mut svg).unwrap();
+/// }
+/// assert_eq!(
+///     s,
+///     r#"<g class="badge" transform="translate(10,10) " >
+///   <circle cx="0" cy="0" r="5" style="fill:gold;" />
+///   <text x="0" y="2" >3</text>
+/// </g>
+/// <g class="badge" transform="translate(20,10) " >
+///   <circle cx="0" cy="0" r="5" style="fill:gold;" />
+///   <text x="0" y="2" >3</text>
+/// </g>
+/// "#
+/// );
+/// ```
 #[derive(Clone, Debug, PartialEq)]
 pub struct Tag {
-    name: &'static str,
+    name: Cow<'static, str>,
     args: Args,
+    children: Vec<Node>,
 }
 
 impl Tag {
@@ -21,6 +59,61 @@ impl Tag {
         Ok(Tag::svg(w, h).wrap(wr))
     }
 
+    /// As start, with a title and optional description for screen readers.
+    ///
+    /// The svg gets role="img" and is labelled by them. Their ids start "svg-title" and
+    /// "svg-desc", numbered if the writer has already used them.
+    ///
+    /// ```
+    /// use mksvg::*;
+    /// let mut s = String::new();
+    /// {
+    ///     let mut w = SvgFmt::new(&mut s);
+    ///     let mut svg = Tag::start_titled(&mut w, 10, 10, "Cards", Some("Page 1")).unwrap();
+    ///     Tag::rect(0, 0, 10, 10).write(&mut svg).unwrap();
+    ///     svg.finish().unwrap();
+    ///     Tag::start_titled(&mut w, 10, 10, "More", None).unwrap();
+    /// }
+    /// assert!(s.contains(r#"role="img" aria-labelledby="svg-title svg-desc" >"#));
+    /// assert!(s.contains("<title id=\"svg-title\" >Cards</title>\n  <desc id=\"svg-desc\" >Page 1</desc>"));
+    /// assert!(s.contains(r#"role="img" aria-labelledby="svg-title_1" >"#));
+    /// ```
+    pub fn start_titled<'a, W: SvgWrite<Err = E>, T: Display, E>(
+        wr: &'a mut W,
+        w: T,
+        h: T,
+        title: &str,
+        desc: Option<&str>,
+    ) -> Result<TransWrap<'a, E>, E> {
+        wr.write(r#"<?xml version="1.0" ?>"#)?;
+        let mut ids = new_id(wr, "svg-title");
+        let mut svg = Tag::svg(w, h)
+            .role("img")
+            .child(Tag::title().id(&ids).text(title));
+        if let Some(d) = desc {
+            let d_id = new_id(wr, "svg-desc");
+            svg = svg.child(Tag::desc().id(&d_id).text(d));
+            ids.push(' ');
+            ids.push_str(&d_id);
+        }
+        Ok(svg.aria_labelledby(ids).wrap(wr))
+    }
+
+    /// As start, also declaring the given namespaces on the svg element
+    pub fn start_ns<'a, W: SvgWrite<Err = E>, T: Display, E>(
+        wr: &'a mut W,
+        w: T,
+        h: T,
+        nss: &[Ns],
+    ) -> Result<TransWrap<'a, E>, E> {
+        wr.write(r#"<?xml version="1.0" ?>"#)?;
+        let mut svg = Tag::svg(w, h);
+        for n in nss {
+            svg = svg.arg(&format!("xmlns:{}", n.prefix()), n.uri());
+        }
+        Ok(svg.wrap(wr))
+    }
+
     fn svg<T: Display>(w: T, h: T) -> Self {
         Tag::new("svg")
             .w(w)
@@ -28,10 +121,12 @@ impl Tag {
             .arg("xmlns", "http://www.w3.org/2000/svg")
             .arg("xmlns:xlink", "http://www.w3.org/1999/xlink")
     }
-    pub fn new(name: &'static str) -> Self {
+    /// the name can be any string, including prefixed names like "inkscape:grid"
+    pub fn new<N: Into<Cow<'static, str>>>(name: N) -> Self {
         Tag {
-            name,
+            name: name.into(),
             args: Args::new(),
+            children: Vec::new(),
         }
     }
     pub fn rect<P: Display, S: Display>(x: P, y: P, w: S, h: S) -> Self {
@@ -63,16 +158,193 @@ impl Tag {
         Tag::new("clipPath")
     }
 
+    pub fn circle<P: Display, S: Display>(cx: P, cy: P, r: S) -> Self {
+        Tag::new("circle").cx(cx).cy(cy).r(r)
+    }
+
+    pub fn line<P: Display>(x1: P, y1: P, x2: P, y2: P) -> Self {
+        Tag::new("line").x1(x1).y1(y1).x2(x2).y2(y2)
+    }
+
+    /// ```
+    /// use mksvg::Tag;
+    /// assert_eq!(
+    ///     Tag::polyline(&[(0, 0), (5, 10)]).to_string(),
+    ///     r#"<polyline points="0,0 5,10" />"#
+    /// );
+    /// ```
+    pub fn polyline<P: Display>(pts: &[(P, P)]) -> Self {
+        Tag::new("polyline").points(pts)
+    }
+
+    pub fn polygon<P: Display>(pts: &[(P, P)]) -> Self {
+        Tag::new("polygon").points(pts)
+    }
+
+    /// the text element, to write lines of text see the Text type
+    pub fn text_tag<P: Display>(x: P, y: P) -> Self {
+        Tag::new("text").xy(x, y)
+    }
+
+    pub fn tspan() -> Self {
+        Tag::new("tspan")
+    }
+
+    pub fn symbol<T: Display>(id: T) -> Self {
+        Tag::new("symbol").id(id)
+    }
+
+    pub fn marker<T: Display>(id: T) -> Self {
+        Tag::new("marker").id(id)
+    }
+
+    pub fn mask<T: Display>(id: T) -> Self {
+        Tag::new("mask").id(id)
+    }
+
+    pub fn pattern<T: Display, P: Display, S: Display>(id: T, x: P, y: P, w: S, h: S) -> Self {
+        Tag::new("pattern").id(id).xy(x, y).wh(w, h)
+    }
+
+    pub fn title() -> Self {
+        Tag::new("title")
+    }
+
+    pub fn desc() -> Self {
+        Tag::new("desc")
+    }
+
+    pub fn a<T: Display>(href: T) -> Self {
+        Tag::new("a").href(href)
+    }
+
+    pub fn switch() -> Self {
+        Tag::new("switch")
+    }
+
+    pub fn foreign_object<P: Display, S: Display>(x: P, y: P, w: S, h: S) -> Self {
+        Tag::new("foreignObject").xy(x, y).wh(w, h)
+    }
+
+    pub fn style_tag() -> Self {
+        Tag::new("style")
+    }
+
+    /// merge in a set of arguments, see Args::merge
+    pub fn merge(mut self, a: &Args) -> Self {
+        self.args = self.args.merge(a);
+        self
+    }
+
+    /// see Args::style_mode
+    pub fn style_mode(mut self, m: StyleMode) -> Self {
+        self.args = self.args.style_mode(m);
+        self
+    }
+
+    /// add an argument that will not be escaped, see Args::raw_arg
+    pub fn raw_arg<T: Display>(mut self, k: &str, v: T) -> Self {
+        self.args = self.args.raw_arg(k, v);
+        self
+    }
+
+    /// add a child element or node
+    pub fn child<N: Into<Node>>(mut self, n: N) -> Self {
+        self.children.push(n.into());
+        self
+    }
+
+    /// add text content, this is escaped when written
+    pub fn text<T: Display>(mut self, t: T) -> Self {
+        self.children.push(Node::Text(t.to_string()));
+        self
+    }
+
+    /// add a title with the given id, as its first child, and label the element by it
+    pub fn with_title<I: Display, T: Display>(mut self, id: I, t: T) -> Self {
+        let id = id.to_string();
+        self.children.insert(0, Tag::title().id(&id).text(t).into());
+        self.aria_labelledby(id)
+    }
+
+    /// add a desc, the accessible description, after any title
+    pub fn with_desc<T: Display>(mut self, d: T) -> Self {
+        let pos = match self.children.first() {
+            Some(Node::Elem(e)) if e.name == "title" => 1,
+            _ => 0,
+        };
+        self.children.insert(pos, Tag::desc().text(d).into());
+        self
+    }
+
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The args with declarations added for any namespaces used by the tag or its
+    /// children, that the writer has not declared, and the namespaces it declares.
+    fn ns_args<W: SvgWrite<Err = E>, E>(&self, w: &W) -> (Cow<'_, Args>, Vec<Ns>) {
+        let mut args = Cow::Borrowed(&self.args);
+        let mut declared: Vec<Ns> = ns::ALL
+            .iter()
+            .filter(|n| self.args.get(&format!("xmlns:{}", n.prefix())).is_some())
+            .copied()
+            .collect();
+        for n in ns::used(&self.name, &self.args, &self.children) {
+            if !declared.contains(&n) && !w.ns_declared(n) {
+                let k = format!("xmlns:{}", n.prefix());
+                args = Cow::Owned(args.into_owned().arg(&k, n.uri()));
+                declared.push(n);
+            }
+        }
+        (args, declared)
+    }
+
+    /// write the tag and all of its children
     pub fn write<W: SvgWrite<Err = E>, E>(&self, w: &mut W) -> Result<(), E> {
-        w.write(&self.to_string())
+        let (args, _) = self.ns_args(w);
+        write_tree(&self.name, &args, &self.children, w)
     }
 
+    /// Open the tag, so more can be written inside it. Any children are written first,
+    /// an error writing them is passed to the writer's defer_err.
     pub fn wrap<'a, W: SvgWrite<Err = E>, E>(&self, w: &'a mut W) -> TransWrap<'a, E> {
-        TransWrap::new(
-            w,
-            &format!("<{} {}>", self.name, self.args),
-            &format!("</{}>", self.name),
-        )
+        let (args, declared) = self.ns_args(w);
+        let start = w.format().tag(w.depth(), &self.name, &args, ">");
+        let mut tw = TransWrap::new(w, &start, &format!("</{}>", self.name)).declaring(declared);
+        for c in &self.children {
+            if let Err(e) = c.write(&mut tw) {
+                tw.defer_err(e);
+                break;
+            }
+        }
+        tw
+    }
+}
+
+impl From<Tag> for Element {
+    fn from(t: Tag) -> Self {
+        Element {
+            name: t.name.into_owned(),
+            args: t.args,
+            children: t.children,
+        }
+    }
+}
+
+impl From<Tag> for Node {
+    fn from(t: Tag) -> Self {
+        Node::Elem(t.into())
+    }
+}
+
+impl From<PathD> for Tag {
+    fn from(p: PathD) -> Self {
+        Tag::path(p)
     }
 }
 
@@ -93,6 +365,158 @@ impl SvgArg for Tag {
 
 impl Display for Tag {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.children.is_empty() {
+            return self.write(&mut SvgFmt::new(f));
+        }
         write!(f, "<{} {}/>", self.name, self.args)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::validate::Validator;
+    use crate::write::SvgFmt;
+
+    #[test]
+    pub fn test_constructors_write_valid_svg() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 100, 100).unwrap();
+            {
+                let mut defs = Tag::defs().wrap(&mut svg);
+                Tag::symbol("s")
+                    .view_box(0, 0, 10, 10)
+                    .write(&mut defs)
+                    .unwrap();
+                Tag::marker("m").write(&mut defs).unwrap();
+                Tag::mask("k").write(&mut defs).unwrap();
+                Tag::pattern("p", 0, 0, 4, 4).write(&mut defs).unwrap();
+                Tag::style_tag().write(&mut defs).unwrap();
+            }
+            Tag::title().write(&mut svg).unwrap();
+            Tag::desc().write(&mut svg).unwrap();
+            Tag::circle(5, 5, 2).write(&mut svg).unwrap();
+            Tag::line(0, 0, 10, 10).write(&mut svg).unwrap();
+            Tag::polygon(&[(0, 0), (1, 1), (0, 1)])
+                .write(&mut svg)
+                .unwrap();
+            let mut a = Tag::a("http://x.org").wrap(&mut svg);
+            let mut sw = Tag::switch().wrap(&mut a);
+            Tag::foreign_object(0, 0, 5, 5).write(&mut sw).unwrap();
+            let mut t = Tag::text_tag(1, 2).wrap(&mut sw);
+            Tag::tspan().write(&mut t).unwrap();
+        }
+        assert!(v.report().is_ok());
+        assert!(s.contains(r#"<circle cx="5" cy="5" r="2" />"#));
+        assert!(s.contains(r#"<line x1="0" y1="0" x2="10" y2="10" />"#));
+        assert!(s.contains(r#"<polygon points="0,0 1,1 0,1" />"#));
+    }
+
+    #[test]
+    pub fn test_overrides_and_merge() {
+        let defaults = Args::new().fill("white").stroke("black").stroke_width(2);
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 10, 10).unwrap();
+            Tag::rect(0, 0, 5, 5)
+                .merge(&defaults)
+                .x(2)
+                .fill("red")
+                .write(&mut svg)
+                .unwrap();
+        }
+        assert!(v.report().is_ok());
+        assert!(s.contains(
+            r#"<rect x="2" y="0" width="5" height="5" style="fill:red;stroke:black;stroke-width:2;" />"#
+        ));
+    }
+
+    #[test]
+    pub fn test_style_and_transform_args_combine() {
+        let a = Args::new().arg("style", "fill:red").stroke("blue");
+        assert_eq!(a.to_string(), r#"style="fill:red;stroke:blue;" "#);
+        let t = Tag::rect(0, 0, 1, 1)
+            .arg("transform", "rotate(3)")
+            .translate(1, 2)
+            .arg("style", "fill:red;stroke:green")
+            .fill("blue");
+        assert_eq!(
+            t.to_string(),
+            "<rect x=\"0\" y=\"0\" width=\"1\" height=\"1\" style=\"fill:blue;stroke:green;\" transform=\"rotate(3) translate(1,2) \" />"
+        );
+    }
+
+    #[test]
+    pub fn test_tree_wrap_and_doc() {
+        use crate::doc::SvgDoc;
+        let t = Tag::g()
+            .id("box")
+            .child(Tag::rect(0, 0, 4, 4))
+            .child(Tag::title().text("a < b"));
+        let mut doc = SvgDoc::new();
+        {
+            let mut g = t.wrap(&mut doc);
+            Tag::circle(1, 1, 1).write(&mut g).unwrap();
+        }
+        let e = doc.find_by_id("box").unwrap();
+        assert_eq!(e.children.len(), 3);
+        assert_eq!(e.children[1], Tag::title().text("a < b").into());
+        assert_eq!(
+            t.to_string(),
+            "<g id=\"box\" >\n  <rect x=\"0\" y=\"0\" width=\"4\" height=\"4\" />\n  <title >a &lt; b</title>\n</g>\n"
+        );
+    }
+
+    #[test]
+    pub fn test_dynamic_names_and_ns() {
+        let name = String::from("inkscape:grid");
+        let grid = Tag::new(name).arg("spacingx", 5);
+        let mut s = String::new();
+        {
+            let mut w = SvgFmt::new(&mut s);
+            let mut nv = Tag::new("sodipodi:namedview")
+                .arg("inkscape:zoom", 2)
+                .wrap(&mut w);
+            grid.write(&mut nv).unwrap();
+        }
+        assert_eq!(
+            s,
+            r#"<sodipodi:namedview inkscape:zoom="2" xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" >
+  <inkscape:grid spacingx="5" />
+</sodipodi:namedview>
+"#
+        );
+        let mut s = String::new();
+        {
+            let mut w = SvgFmt::new(&mut s);
+            let mut svg = Tag::start(&mut w, 5, 5).unwrap();
+            Tag::rect(0, 0, 5, 5).write(&mut svg).unwrap();
+        }
+        assert!(s.contains("<rect") && !s.contains("inkscape"));
+    }
+
+    #[test]
+    pub fn test_title_and_desc_come_first() {
+        let t = Tag::g()
+            .child(Tag::rect(0, 0, 1, 1))
+            .with_desc("a square")
+            .with_title("sq-title", "Square")
+            .role("group");
+        let names: Vec<&str> = t
+            .children()
+            .iter()
+            .map(|c| match c {
+                Node::Elem(e) => e.name.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(names, vec!["title", "desc", "rect"]);
+        assert!(t.to_string().contains(
+            r#"<g aria-labelledby="sq-title" role="group" >
+  <title id="sq-title" >Square</title>"#
+        ));
+    }
+}
@@ -0,0 +1,257 @@
+//! Helpers for testing svg output without depending on its exact layout.
+//!
+//! canonical rewrites svg so that whitespace, argument order, style property order and
+//! number formatting no longer matter, diff compares two svgs that way and describes
+//! the difference, and the snapshot functions compare output with a file on disk.
+//!
+//! ```
+//! use mksvg::assert_svg_eq;
+//! assert_svg_eq!(
+//!     r#"<g><rect x="0" y="1.50" style="stroke:red;fill:blue;"/></g>"#,
+//!     "<g >\n  <rect style=\"fill:blue;stroke:red\" y=\"1.5\" x=\"0\" />\n</g>"
+//! );
+//! ```
+//!
+//! To create or update snapshot files, run the tests with MKSVG_UPDATE_SNAPSHOTS set.
+
+use crate::doc::{Element, Node};
+use crate::err::ParseError;
+use crate::parse::parse_str;
+use std::path::Path;
+
+pub const UPDATE_VAR: &str = "MKSVG_UPDATE_SNAPSHOTS";
+
+/// Write a number in its shortest form, "1.50" becomes "1.5", "2.0" becomes "2"
+fn canon_num(s: &str) -> Option<String> {
+    let split = s
+        .find(|c: char| (c.is_ascii_alphabetic() && c != 'e' && c != 'E') || c == '%')
+        .unwrap_or(s.len());
+    let (n, unit) = s.split_at(split);
+    if !unit.chars().all(|c| c.is_ascii_alphabetic() || c == '%') {
+        return None;
+    }
+    let f: f64 = n.parse().ok()?;
+    let f = if f == 0.0 { 0.0 } else { f };
+    Some(format!("{}{}", f, unit))
+}
+
+/// canonical numbers for every separate token of a value
+fn canon_value(v: &str) -> String {
+    let mut res = String::new();
+    let mut tok = String::new();
+    let flush = |tok: &mut String, res: &mut String| {
+        match canon_num(tok) {
+            Some(n) => res.push_str(&n),
+            None => res.push_str(tok),
+        }
+        tok.clear();
+    };
+    for c in v.trim().chars() {
+        match c {
+            ' ' | '\t' | '\n' | ',' | '(' | ')' | ';' | ':' => {
+                flush(&mut tok, &mut res);
+                res.push(c);
+            }
+            _ => tok.push(c),
+        }
+    }
+    flush(&mut tok, &mut res);
+    res
+}
+
+fn canon_element(e: &Element, depth: usize, res: &mut Vec<String>) {
+    let pad = "  ".repeat(depth);
+    let mut args: Vec<(String, String)> = e
+        .args
+        .args()
+        .map(|(k, v)| (k.to_string(), canon_value(v)))
+        .collect();
+    let mut styles: Vec<String> = e
+        .args
+        .styles()
+        .map(|(k, v)| format!("{}:{}", k, canon_value(v)))
+        .collect();
+    if !styles.is_empty() {
+        styles.sort();
+        args.push(("style".to_string(), styles.join(";")));
+    }
+    let trans: Vec<String> = e
+        .args
+        .transforms()
+        .map(|(k, v)| format!("{}({})", k, canon_value(v)))
+        .collect();
+    if !trans.is_empty() {
+        args.push(("transform".to_string(), trans.join(" ")));
+    }
+    args.sort();
+    let mut open = format!("{}<{}", pad, e.name);
+    for (k, v) in args {
+        open.push_str(&format!(" {}=\"{}\"", k, v));
+    }
+    let children: Vec<&Node> = e.children.iter().filter(|n| keep(n)).collect();
+    if children.is_empty() {
+        open.push_str("/>");
+        res.push(open);
+        return;
+    }
+    open.push('>');
+    res.push(open);
+    for c in children {
+        canon_node(c, depth + 1, res);
+    }
+    res.push(format!("{}</{}>", pad, e.name));
+}
+
+/// comments and the xml declaration do not affect comparison
+fn keep(n: &Node) -> bool {
+    match n {
+        Node::Raw(r) => !r.starts_with("<!--") && !r.starts_with("<?xml"),
+        _ => true,
+    }
+}
+
+fn canon_node(n: &Node, depth: usize, res: &mut Vec<String>) {
+    let pad = "  ".repeat(depth);
+    match n {
+        Node::Elem(e) => canon_element(e, depth, res),
+        Node::Text(t) => res.push(format!(
+            "{}{}",
+            pad,
+            t.split_whitespace().collect::<Vec<&str>>().join(" ")
+        )),
+        Node::Raw(r) => res.push(format!("{}{}", pad, r)),
+    }
+}
+
+/// the canonical form of an svg document or fragment, one node per line.
+pub fn canonical(s: &str) -> Result<String, ParseError> {
+    let doc = parse_str(s)?;
+    let mut res = Vec::new();
+    for n in doc.nodes().iter().filter(|n| keep(n)) {
+        canon_node(n, 0, &mut res);
+    }
+    Ok(res.join("\n"))
+}
+
+/// a line by line diff of a and b, "-" lines are only in a, "+" lines only in b.
+fn line_diff(a: &[&str], b: &[&str]) -> String {
+    let (n, m) = (a.len(), b.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = match a[i] == b[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+    let mut res = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            res.push_str(&format!("  {}\n", a[i]));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            res.push_str(&format!("+ {}\n", b[j]));
+            j += 1;
+        } else {
+            res.push_str(&format!("- {}\n", a[i]));
+            i += 1;
+        }
+    }
+    res
+}
+
+/// Compare two svgs canonically, returning None if they match,
+/// or a description of the difference.
+pub fn diff(a: &str, b: &str) -> Option<String> {
+    let (ca, cb) = match (canonical(a), canonical(b)) {
+        (Ok(ca), Ok(cb)) => (ca, cb),
+        (Err(e), _) => return Some(format!("left is not valid svg: {}", e)),
+        (_, Err(e)) => return Some(format!("right is not valid svg: {}", e)),
+    };
+    if ca == cb {
+        return None;
+    }
+    let la: Vec<&str> = ca.lines().collect();
+    let lb: Vec<&str> = cb.lines().collect();
+    Some(line_diff(&la, &lb))
+}
+
+/// Panics with a diff if the two svgs are not canonically equal
+#[macro_export]
+macro_rules! assert_svg_eq {
+    ($a:expr, $b:expr) => {
+        if let Some(d) = $crate::testing::diff(&$a, &$b) {
+            panic!("svgs differ (- left, + right):\n{}", d);
+        }
+    };
+}
+
+/// Compare actual with the snapshot file at path.
+///
+/// If the file does not exist, or MKSVG_UPDATE_SNAPSHOTS is set, it is
+/// written with actual and the check passes.
+pub fn check_snapshot<P: AsRef<Path>>(path: P, actual: &str) -> Result<(), String> {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_VAR).is_some() || !path.exists() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        return std::fs::write(path, actual).map_err(|e| e.to_string());
+    }
+    let expected = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    match diff(&expected, actual) {
+        None => Ok(()),
+        Some(d) => Err(format!(
+            "snapshot {} differs (- snapshot, + actual), set {} to update:\n{}",
+            path.display(),
+            UPDATE_VAR,
+            d
+        )),
+    }
+}
+
+/// Panics if actual does not match the snapshot file, see check_snapshot
+pub fn assert_snapshot<P: AsRef<Path>>(path: P, actual: &str) {
+    if let Err(e) = check_snapshot(path, actual) {
+        panic!("{}", e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn test_canonical() {
+        let c = canonical(
+            "<?xml version=\"1.0\" ?>\n<svg height=\"20.0\" width=\"20\">\n<!-- hi -->\n <g transform=\"translate(3.0 4) scale(2)\" fill=\"#ff0000\">  some\n  text </g></svg>",
+        )
+        .unwrap();
+        assert_eq!(
+            c,
+            "<svg height=\"20\" width=\"20\">\n  <g fill=\"#ff0000\" transform=\"translate(3,4) scale(2)\">\n    some text\n  </g>\n</svg>"
+        );
+    }
+
+    #[test]
+    pub fn test_diff() {
+        assert_eq!(diff("<g><a x='1.0'/></g>", "<g><a x=\"1\" /></g>"), None);
+        assert_eq!(
+            diff("<g><a/><b/></g>", "<g><a/><c/></g>").unwrap(),
+            "  <g>\n    <a/>\n+   <c/>\n-   <b/>\n  </g>\n"
+        );
+    }
+
+    #[test]
+    pub fn test_snapshot() {
+        let dir = std::env::temp_dir().join(format!("mksvg_snap_{}", std::process::id()));
+        let p = dir.join("snap.svg");
+        assert!(check_snapshot(&p, "<g><rect x=\"1\"/></g>").is_ok());
+        assert!(check_snapshot(&p, "<g >\n  <rect x=\"1.0\" />\n</g>").is_ok());
+        assert!(check_snapshot(&p, "<g><rect x=\"2\"/></g>").is_err());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
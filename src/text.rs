@@ -3,6 +3,8 @@
 //!
 
 use crate::args::{Args, SvgArg};
+use crate::escape::escape_text;
+use crate::tag::Tag;
 use crate::write::{qcast, CDNum, SvgWrite};
 use std::fmt;
 use std::fmt::{Debug, Display};
@@ -16,6 +18,8 @@ pub struct Text<C: CDNum> {
     y: C,
     line_height: C,
     font_size_set: bool,
+    raw: bool,
+    label: bool,
 }
 
 impl<C: CDNum> Text<C> {
@@ -37,6 +41,8 @@ impl<C: CDNum> Text<C> {
             y,
             line_height: lh,
             font_size_set: false,
+            raw: false,
+            label: false,
         }
     }
 
@@ -65,8 +71,59 @@ impl<C: CDNum> Text<C> {
         self
     }
 
+    /// Do not escape the lines, for text that already contains markup such as tspans
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+
+    /// Group the lines, labelled with the whole text, so screen readers read it
+    /// once, rather than as separate lines and backgrounds.
+    ///
+    /// ```
+    /// use mksvg::*;
+    /// let mut s = String::new();
+    /// Text::new("Draw a\ncard", 0, 0, 5).accessible().write(&mut SvgFmt::new(&mut s)).unwrap();
+    /// assert_eq!(
+    ///     s,
+    ///     r#"<g role="img" aria-label="Draw a card" >
+    ///   <text aria-hidden="true" x="0" y="0" style="font-size:5;" >Draw a</text>
+    ///   <text aria-hidden="true" x="0" y="5" style="font-size:5;" >card</text>
+    /// </g>
+    /// "#
+    /// );
+    /// ```
+    pub fn accessible(mut self) -> Self {
+        self.label = true;
+        self
+    }
+
+    /// the full text, with lines joined by spaces
+    pub fn full_text(&self) -> String {
+        self.ss
+            .iter()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
     pub fn write<E, S: SvgWrite<Err = E>>(&self, s: &mut S) -> Result<(), E> {
+        if self.label {
+            let mut g = Tag::g().role("img").aria_label(self.full_text()).wrap(s);
+            let mut t = self.clone();
+            t.label = false;
+            t.args = t.args.aria_hidden();
+            t.write(&mut g)?;
+            return g.finish();
+        }
+        let f = s.format();
+        let d = s.depth();
         for (n, l) in self.ss.iter().enumerate() {
+            let l = match self.raw {
+                true => l.as_str().into(),
+                false => escape_text(l),
+            };
             let mut a = self.args.clone();
             if !self.font_size_set {
                 a = a.font_size(self.line_height)
@@ -74,9 +131,9 @@ impl<C: CDNum> Text<C> {
             a = a.xy(self.x, self.y + self.line_height * qcast(n));
             if let Some((w, ref col)) = self.back {
                 let a2 = a.clone().stroke_width(w).stroke(col);
-                s.write(&format!("<text {}>{}</text>", a2, l))?;
+                s.write(&format!("{}{}</text>", f.tag(d, "text", &a2, ">"), l))?;
             }
-            s.write(&format!("<text {}>{}</text>", a, l))?;
+            s.write(&format!("{}{}</text>", f.tag(d, "text", &a, ">"), l))?;
         }
         Ok(())
     }
@@ -161,7 +218,7 @@ pub fn wrap(s: &str, mx: usize) -> Vec<String> {
 
     for c in s.chars() {
         if cline.len() + cword.len() > mx {
-            if cline.len() == 0 {
+            if cline.is_empty() {
                 cline.push_str(&cword[..mx]);
                 cline.push('-');
                 cword = String::from(&cword[mx..]);
@@ -0,0 +1,237 @@
+//! A numeric 2D affine transform, for working out where things end up.
+//!
+//! Transforms compose with "*" in the same order as a transform list, so
+//! `a * b` is the same as `transform="a b"`, b is applied to points first.
+//! Use SvgArg::transform_by to set one on anything that takes transforms.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::transform::Transform;
+//! let t = Transform::translate(10., 0.) * Transform::rotate(90.);
+//! assert_eq!(t.apply(1., 0.), (10., 1.));
+//! assert_eq!(t.inverse().unwrap().apply(10., 1.), (1., 0.));
+//! let parsed: Transform = "translate(10) rotate(90)".parse().unwrap();
+//! assert_eq!(parsed, t);
+//! assert_eq!(
+//!     Tag::rect(0, 0, 5, 5).transform_by(&t).to_string(),
+//!     r#"<rect x="0" y="0" width="5" height="5" transform="matrix(0,1,-1,0,10,0) " />"#
+//! );
+//! ```
+
+use crate::err::TransformError;
+use std::fmt;
+use std::fmt::Display;
+use std::ops::Mul;
+use std::str::FromStr;
+
+/// The matrix [a c e, b d f, 0 0 1], as in svg's matrix(a,b,c,d,e,f)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+/// Round away float noise, such as cos(90) not quite being 0
+fn tidy(v: f64) -> f64 {
+    let r = (v * 1e9).round() / 1e9;
+    match (v - r).abs() < 1e-12 {
+        true => r + 0.,
+        false => v,
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl Transform {
+    pub fn matrix(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Transform { a, b, c, d, e, f }
+    }
+
+    pub fn identity() -> Self {
+        Transform::matrix(1., 0., 0., 1., 0., 0.)
+    }
+
+    pub fn translate(x: f64, y: f64) -> Self {
+        Transform::matrix(1., 0., 0., 1., x, y)
+    }
+
+    pub fn scale(x: f64, y: f64) -> Self {
+        Transform::matrix(x, 0., 0., y, 0., 0.)
+    }
+
+    /// Rotate clockwise by degrees, about the origin
+    pub fn rotate(deg: f64) -> Self {
+        let (s, c) = deg.to_radians().sin_cos();
+        Transform::matrix(tidy(c), tidy(s), tidy(-s), tidy(c), 0., 0.)
+    }
+
+    /// Rotate by degrees about the point x,y
+    pub fn rotate_around(deg: f64, x: f64, y: f64) -> Self {
+        Transform::translate(x, y) * Transform::rotate(deg) * Transform::translate(-x, -y)
+    }
+
+    pub fn skew_x(deg: f64) -> Self {
+        Transform::matrix(1., 0., tidy(deg.to_radians().tan()), 1., 0., 0.)
+    }
+
+    pub fn skew_y(deg: f64) -> Self {
+        Transform::matrix(1., tidy(deg.to_radians().tan()), 0., 1., 0., 0.)
+    }
+
+    /// Apply self, then next
+    pub fn then(&self, next: &Transform) -> Self {
+        *next * *self
+    }
+
+    /// None if the transform squashes everything onto a line or point
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let Transform { a, b, c, d, e, f } = *self;
+        Some(Transform::matrix(
+            d / det,
+            -b / det,
+            -c / det,
+            a / det,
+            (c * f - d * e) / det,
+            (b * e - a * f) / det,
+        ))
+    }
+
+    /// Where the point x,y ends up
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            tidy(self.a * x + self.c * y + self.e),
+            tidy(self.b * x + self.d * y + self.f),
+        )
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Transform::identity()
+    }
+
+    /// [a,b,c,d,e,f] with float noise rounded away
+    pub fn to_array(&self) -> [f64; 6] {
+        [
+            tidy(self.a),
+            tidy(self.b),
+            tidy(self.c),
+            tidy(self.d),
+            tidy(self.e),
+            tidy(self.f),
+        ]
+    }
+}
+
+impl Mul for Transform {
+    type Output = Transform;
+    fn mul(self, o: Transform) -> Transform {
+        Transform::matrix(
+            self.a * o.a + self.c * o.b,
+            self.b * o.a + self.d * o.b,
+            self.a * o.c + self.c * o.d,
+            self.b * o.c + self.d * o.d,
+            self.a * o.e + self.c * o.f + self.e,
+            self.b * o.e + self.d * o.f + self.f,
+        )
+    }
+}
+
+/// As a transform list, "matrix(a,b,c,d,e,f)"
+impl Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let v = self.to_array();
+        write!(
+            f,
+            "matrix({},{},{},{},{},{})",
+            v[0], v[1], v[2], v[3], v[4], v[5]
+        )
+    }
+}
+
+fn parse_one(name: &str, v: &[f64]) -> Option<Transform> {
+    Some(match (name, v.len()) {
+        ("matrix", 6) => Transform::matrix(v[0], v[1], v[2], v[3], v[4], v[5]),
+        ("translate", 1) => Transform::translate(v[0], 0.),
+        ("translate", 2) => Transform::translate(v[0], v[1]),
+        ("scale", 1) => Transform::scale(v[0], v[0]),
+        ("scale", 2) => Transform::scale(v[0], v[1]),
+        ("rotate", 1) => Transform::rotate(v[0]),
+        ("rotate", 3) => Transform::rotate_around(v[0], v[1], v[2]),
+        ("skewX", 1) => Transform::skew_x(v[0]),
+        ("skewY", 1) => Transform::skew_y(v[0]),
+        _ => return None,
+    })
+}
+
+/// Parses a transform list, as found in a transform argument
+impl FromStr for Transform {
+    type Err = TransformError;
+    fn from_str(s: &str) -> Result<Self, TransformError> {
+        let err = |m: &str| TransformError(format!("{} in '{}'", m, s));
+        let mut res = Transform::identity();
+        let mut rest = s.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        while !rest.is_empty() {
+            let open = rest.find('(').ok_or_else(|| err("expected '('"))?;
+            let close = open + rest[open..].find(')').ok_or_else(|| err("expected ')'"))?;
+            let name = rest[..open].trim();
+            let v = rest[open + 1..close]
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|n| !n.is_empty())
+                .map(|n| n.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| err("bad number"))?;
+            let t = parse_one(name, &v).ok_or_else(|| err(&format!("bad {}", name)))?;
+            res = res * t;
+            rest = rest[close + 1..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::args::{Args, SvgArg};
+
+    #[test]
+    pub fn test_compose_and_invert() {
+        let t = Transform::translate(3., 4.)
+            .then(&Transform::scale(2., 2.))
+            .then(&Transform::skew_x(45.));
+        let (x, y) = t.apply(1., 1.);
+        assert_eq!((x, y), (18., 10.));
+        let i = t.inverse().unwrap();
+        assert_eq!(i.apply(x, y), (1., 1.));
+        assert_eq!((t * i).to_array(), Transform::identity().to_array());
+        assert_eq!(Transform::scale(0., 1.).inverse(), None);
+        let r = Transform::rotate_around(180., 5., 5.);
+        assert_eq!(r.apply(0., 0.), (10., 10.));
+        assert_eq!(Transform::rotate(90.).to_string(), "matrix(0,1,-1,0,0,0)");
+    }
+
+    #[test]
+    pub fn test_parse() {
+        let t: Transform = "matrix(1,0,0,1,-10,5.5)".parse().unwrap();
+        assert_eq!(t, Transform::translate(-10., 5.5));
+        let t: Transform = " scale(2) , translate(1 -1)skewY(0)".parse().unwrap();
+        assert_eq!(t.apply(0., 0.), (2., -2.));
+        assert_eq!("".parse::<Transform>(), Ok(Transform::identity()));
+        assert!("rotate(1,2)".parse::<Transform>().is_err());
+        assert!("spin(1)".parse::<Transform>().is_err());
+        assert!("scale(x)".parse::<Transform>().is_err());
+        assert!("scale(1".parse::<Transform>().is_err());
+        let a = Args::new().matrix(&[1, 0, 0, 1, 2, 3]);
+        assert_eq!(a.to_string(), r#"transform="matrix(1,0,0,1,2,3) " "#);
+    }
+}
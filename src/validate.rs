@@ -0,0 +1,540 @@
+//! Validator wraps any SvgWrite and checks what is written through it.
+//!
+//! It checks that tags are balanced, element names are known svg elements, element
+//! specific arguments are only used on elements that take them (cx on a rect is wrong),
+//! and that Tag::start was only used for the root.
+//!
+//! By default the first problem is returned as an error from write. Use
+//! Validator::collecting to keep writing and get every problem from report.
+//!
+//! ```
+//! use mksvg::*;
+//! use mksvg::validate::Validator;
+//! use mksvg::err::Invalid;
+//! let mut s = String::new();
+//! let mut v = Validator::collecting(SvgFmt::new(&mut s));
+//! {
+//!     let mut g = Tag::g().wrap(&mut v);
+//!     Tag::rect(0, 0, 5, 5).cx(3).write(&mut g).unwrap();
+//!     Tag::new("blob").write(&mut g).unwrap();
+//! }
+//! assert_eq!(
+//!     v.report().err().unwrap(),
+//!     vec![
+//!         Invalid::BadArg { element: "rect".to_string(), arg: "cx".to_string() },
+//!         Invalid::UnknownElement("blob".to_string()),
+//!     ]
+//! );
+//! ```
+
+use crate::args::Args;
+use crate::err::{Invalid, ValidError};
+use crate::format::Format;
+use crate::ns::Ns;
+use crate::parse::{tokens, Token};
+use crate::resource::{Ids, Resources};
+use crate::write::SvgWrite;
+
+pub const ELEMENTS: &[&str] = &[
+    "a",
+    "animate",
+    "animateMotion",
+    "animateTransform",
+    "circle",
+    "clipPath",
+    "defs",
+    "desc",
+    "ellipse",
+    "feBlend",
+    "feColorMatrix",
+    "feComponentTransfer",
+    "feComposite",
+    "feConvolveMatrix",
+    "feDiffuseLighting",
+    "feDisplacementMap",
+    "feDistantLight",
+    "feDropShadow",
+    "feFlood",
+    "feFuncA",
+    "feFuncB",
+    "feFuncG",
+    "feFuncR",
+    "feGaussianBlur",
+    "feImage",
+    "feMerge",
+    "feMergeNode",
+    "feMorphology",
+    "feOffset",
+    "fePointLight",
+    "feSpecularLighting",
+    "feSpotLight",
+    "feTile",
+    "feTurbulence",
+    "filter",
+    "foreignObject",
+    "g",
+    "image",
+    "line",
+    "linearGradient",
+    "marker",
+    "mask",
+    "metadata",
+    "mpath",
+    "path",
+    "pattern",
+    "polygon",
+    "polyline",
+    "radialGradient",
+    "rect",
+    "script",
+    "set",
+    "stop",
+    "style",
+    "svg",
+    "switch",
+    "symbol",
+    "text",
+    "textPath",
+    "title",
+    "tspan",
+    "use",
+    "view",
+];
+
+/// "fill" is left out, as it is also a presentation argument
+const ANIM: &[&str] = &[
+    "attributeName",
+    "attributeType",
+    "from",
+    "to",
+    "by",
+    "values",
+    "dur",
+    "begin",
+    "end",
+    "repeatCount",
+    "repeatDur",
+    "keyTimes",
+    "keySplines",
+    "calcMode",
+    "additive",
+    "accumulate",
+    "restart",
+    "min",
+    "max",
+    "href",
+];
+
+const FE: &[&str] = &["x", "y", "width", "height", "result", "in", "in2"];
+
+/// Arguments that are specific to some elements, anything not listed here
+/// (presentation arguments, ids, classes etc.) is allowed on any element.
+fn element_args(name: &str) -> Option<(&'static [&'static str], &'static [&'static str])> {
+    let none: &[&str] = &[];
+    let r: (&[&str], &[&str]) = match name {
+        "svg" => (
+            &[
+                "x",
+                "y",
+                "width",
+                "height",
+                "viewBox",
+                "preserveAspectRatio",
+                "version",
+                "baseProfile",
+            ],
+            none,
+        ),
+        "g" | "defs" | "title" | "desc" | "metadata" | "switch" => (none, none),
+        "symbol" => (
+            &[
+                "x",
+                "y",
+                "width",
+                "height",
+                "viewBox",
+                "preserveAspectRatio",
+                "refX",
+                "refY",
+            ],
+            none,
+        ),
+        "use" => (&["x", "y", "width", "height", "href"], none),
+        "image" => (
+            &[
+                "x",
+                "y",
+                "width",
+                "height",
+                "href",
+                "preserveAspectRatio",
+                "crossorigin",
+            ],
+            none,
+        ),
+        "rect" => (
+            &["x", "y", "width", "height", "rx", "ry", "pathLength"],
+            none,
+        ),
+        "circle" => (&["cx", "cy", "r", "pathLength"], none),
+        "ellipse" => (&["cx", "cy", "rx", "ry", "pathLength"], none),
+        "line" => (&["x1", "y1", "x2", "y2", "pathLength"], none),
+        "polyline" | "polygon" => (&["points", "pathLength"], none),
+        "path" => (&["d", "pathLength"], none),
+        "text" | "tspan" => (
+            &["x", "y", "dx", "dy", "rotate", "textLength", "lengthAdjust"],
+            none,
+        ),
+        "textPath" => (
+            &[
+                "href",
+                "startOffset",
+                "method",
+                "spacing",
+                "side",
+                "path",
+                "textLength",
+                "lengthAdjust",
+            ],
+            none,
+        ),
+        "a" => (
+            &[
+                "href",
+                "target",
+                "download",
+                "rel",
+                "hreflang",
+                "type",
+                "referrerpolicy",
+                "ping",
+            ],
+            none,
+        ),
+        "marker" => (
+            &[
+                "viewBox",
+                "preserveAspectRatio",
+                "refX",
+                "refY",
+                "markerUnits",
+                "markerWidth",
+                "markerHeight",
+                "orient",
+            ],
+            none,
+        ),
+        "clipPath" => (&["clipPathUnits"], none),
+        "mask" => (
+            &["x", "y", "width", "height", "maskUnits", "maskContentUnits"],
+            none,
+        ),
+        "pattern" => (
+            &[
+                "x",
+                "y",
+                "width",
+                "height",
+                "patternUnits",
+                "patternContentUnits",
+                "patternTransform",
+                "viewBox",
+                "preserveAspectRatio",
+                "href",
+            ],
+            none,
+        ),
+        "linearGradient" => (
+            &[
+                "x1",
+                "y1",
+                "x2",
+                "y2",
+                "gradientUnits",
+                "gradientTransform",
+                "spreadMethod",
+                "href",
+            ],
+            none,
+        ),
+        "radialGradient" => (
+            &[
+                "cx",
+                "cy",
+                "r",
+                "fx",
+                "fy",
+                "fr",
+                "gradientUnits",
+                "gradientTransform",
+                "spreadMethod",
+                "href",
+            ],
+            none,
+        ),
+        "stop" => (&["offset"], none),
+        "filter" => (
+            &[
+                "x",
+                "y",
+                "width",
+                "height",
+                "filterUnits",
+                "primitiveUnits",
+                "href",
+            ],
+            none,
+        ),
+        "feGaussianBlur" => (&["stdDeviation", "edgeMode"], FE),
+        "feOffset" => (&["dx", "dy"], FE),
+        "feDropShadow" => (&["dx", "dy", "stdDeviation"], FE),
+        "feFlood" | "feMerge" | "feTile" => (none, FE),
+        "feMergeNode" => (&["in"], none),
+        "feComposite" => (&["operator", "k1", "k2", "k3", "k4"], FE),
+        "feColorMatrix" => (&["type", "values"], FE),
+        "feMorphology" => (&["operator", "radius"], FE),
+        "feTurbulence" => (
+            &["baseFrequency", "numOctaves", "seed", "stitchTiles", "type"],
+            FE,
+        ),
+        "feBlend" => (&["mode"], FE),
+        "foreignObject" => (&["x", "y", "width", "height"], none),
+        "style" => (&["type", "media", "title"], none),
+        "script" => (&["type", "href"], none),
+        "animate" | "set" => (ANIM, none),
+        "animateTransform" => (ANIM, &["type"]),
+        "animateMotion" => (ANIM, &["path", "keyPoints", "rotate", "origin"]),
+        "mpath" => (&["href"], none),
+        "view" => (&["viewBox", "preserveAspectRatio"], none),
+        _ => return None,
+    };
+    Some(r)
+}
+
+fn is_specific(k: &str) -> bool {
+    ELEMENTS.iter().any(|e| match element_args(e) {
+        Some((a, b)) => a.contains(&k) || b.contains(&k),
+        None => false,
+    })
+}
+
+/// check the arguments of a single element
+pub fn check_args(name: &str, args: &Args) -> Vec<Invalid> {
+    let (a, b) = match element_args(name) {
+        Some(ab) => ab,
+        None => return Vec::new(),
+    };
+    args.args()
+        .map(|(k, _)| k)
+        .filter(|k| !a.contains(k) && !b.contains(k) && is_specific(k))
+        .map(|k| Invalid::BadArg {
+            element: name.to_string(),
+            arg: k.to_string(),
+        })
+        .collect()
+}
+
+pub struct Validator<W: SvgWrite> {
+    w: W,
+    fail_fast: bool,
+    open: Vec<String>,
+    written: bool,
+    roots: usize,
+    issues: Vec<Invalid>,
+}
+
+impl<W: SvgWrite> Validator<W> {
+    /// A validator that returns the first problem as an error
+    pub fn new(w: W) -> Self {
+        Validator {
+            w,
+            fail_fast: true,
+            open: Vec::new(),
+            written: false,
+            roots: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    /// A validator that keeps writing and collects problems, see report
+    pub fn collecting(w: W) -> Self {
+        Validator {
+            fail_fast: false,
+            ..Self::new(w)
+        }
+    }
+
+    pub fn issues(&self) -> &[Invalid] {
+        &self.issues
+    }
+
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+
+    /// Finish validating, returning the inner writer if there were no problems,
+    /// any tags still open are reported as unclosed.
+    pub fn report(mut self) -> Result<W, Vec<Invalid>> {
+        if !self.open.is_empty() {
+            let open = std::mem::take(&mut self.open);
+            self.issues.push(Invalid::Unclosed(open));
+        }
+        match self.issues.is_empty() {
+            true => Ok(self.w),
+            false => Err(self.issues),
+        }
+    }
+
+    fn check(&mut self, s: &str) -> Vec<Invalid> {
+        let toks = match tokens(s) {
+            Ok(t) => t,
+            Err(e) => return vec![Invalid::Parse(e)],
+        };
+        let mut res = Vec::new();
+        for (_, t) in toks {
+            let el = match t {
+                Token::Raw(r) => {
+                    if r.starts_with("<?xml") && self.written {
+                        res.push(Invalid::StartNotAtRoot);
+                    }
+                    None
+                }
+                Token::Text(_) => None,
+                Token::Close(name) => {
+                    match self.open.pop() {
+                        Some(o) if o == name => {}
+                        Some(o) => res.push(Invalid::Unbalanced {
+                            expected: o,
+                            got: name,
+                        }),
+                        None => res.push(Invalid::UnexpectedClose(name)),
+                    }
+                    None
+                }
+                Token::Open(e) => {
+                    let depth = self.open.len();
+                    self.open.push(e.name.clone());
+                    Some((depth, e))
+                }
+                Token::Empty(e) => Some((self.open.len(), e)),
+            };
+            self.written = true;
+            if let Some((depth, e)) = el {
+                if depth == 0 {
+                    self.roots += 1;
+                    if self.roots > 1 {
+                        res.push(Invalid::MultipleRoots(e.name.clone()));
+                    }
+                }
+                if !e.name.contains(':') && !ELEMENTS.contains(&e.name.as_str()) {
+                    res.push(Invalid::UnknownElement(e.name.clone()));
+                }
+                res.extend(check_args(&e.name, &e.args));
+            }
+        }
+        res
+    }
+}
+
+impl<W: SvgWrite> SvgWrite for Validator<W> {
+    type Err = ValidError<W::Err>;
+    fn write(&mut self, s: &str) -> Result<(), Self::Err> {
+        let mut found = self.check(s);
+        if self.fail_fast && !found.is_empty() {
+            return Err(ValidError::Invalid(found.remove(0)));
+        }
+        self.issues.extend(found);
+        self.w.write(s).map_err(ValidError::Write)
+    }
+    fn inc_depth(&mut self, n: i8) {
+        self.w.inc_depth(n)
+    }
+    fn depth(&self) -> i8 {
+        self.w.depth()
+    }
+    fn format(&self) -> Format {
+        self.w.format()
+    }
+    fn defer_err(&mut self, e: Self::Err) {
+        match e {
+            ValidError::Write(e) => self.w.defer_err(e),
+            ValidError::Invalid(i) => self.issues.push(i),
+        }
+    }
+    fn flush(&mut self) -> Result<(), Self::Err> {
+        self.w.flush().map_err(ValidError::Write)
+    }
+    fn resources(&mut self) -> Option<&mut Resources> {
+        self.w.resources()
+    }
+    fn ids(&mut self) -> Option<&mut Ids> {
+        self.w.ids()
+    }
+    fn ns_declared(&self, ns: Ns) -> bool {
+        self.w.ns_declared(ns)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::args::SvgArg;
+    use crate::tag::Tag;
+    use crate::text::Text;
+    use crate::write::SvgFmt;
+
+    #[test]
+    pub fn test_valid_page_passes() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 20, 20).unwrap();
+            let mut g = Tag::g().translate(3, 4).wrap(&mut svg);
+            Tag::ellipse(1, 1, 2, 2)
+                .arg("fill", "red")
+                .write(&mut g)
+                .unwrap();
+            Text::new("a", 0, 0, 5)
+                .t_anc("middle")
+                .write(&mut g)
+                .unwrap();
+        }
+        assert!(v.report().is_ok());
+    }
+
+    #[test]
+    pub fn test_fail_fast() {
+        let mut s = String::new();
+        let mut v = Validator::new(SvgFmt::new(&mut s));
+        assert!(Tag::rect(0, 0, 1, 1).write(&mut v).is_ok());
+        match Tag::ellipse(0, 0, 1, 1).write(&mut v) {
+            Err(ValidError::Invalid(Invalid::MultipleRoots(n))) => assert_eq!(n, "ellipse"),
+            r => panic!("expected multiple roots, got {:?}", r),
+        }
+    }
+
+    #[test]
+    pub fn test_start_and_balance() {
+        let mut s = String::new();
+        let mut v = Validator::collecting(SvgFmt::new(&mut s));
+        {
+            let mut svg = Tag::start(&mut v, 20, 20).unwrap();
+            let mut inner = Tag::start(&mut svg, 5, 5).unwrap();
+            inner.write("</g>").unwrap();
+        }
+        v.write("<g>").unwrap();
+        assert_eq!(
+            v.report().err().unwrap(),
+            vec![
+                Invalid::StartNotAtRoot,
+                Invalid::Unbalanced {
+                    expected: "svg".to_string(),
+                    got: "g".to_string()
+                },
+                Invalid::UnexpectedClose("svg".to_string()),
+                Invalid::MultipleRoots("g".to_string()),
+                Invalid::Unclosed(vec!["g".to_string()]),
+            ]
+        );
+    }
+}
@@ -1,3 +1,7 @@
+use crate::escape::escape_text;
+use crate::format::Format;
+use crate::ns::Ns;
+use crate::resource::{Ids, Resources};
 use num;
 use std::fmt::{Debug, Display};
 
@@ -26,30 +30,56 @@ pub fn qcast<A: CDNum, B: CDNum>(a: A) -> B {
 pub struct SvgIO<W: IOWrite> {
     w: W,
     d: i8,
+    f: Format,
+    deferred: Option<std::io::Error>,
+    ids: Ids,
 }
 
 impl<W: IOWrite> SvgIO<W> {
     pub fn new(w: W) -> SvgIO<W> {
-        SvgIO { w: w, d: 0 }
+        Self::with_format(w, Format::default())
     }
-    fn pad(&self) -> String {
-        let mut res = "".to_string();
-        for _i in 0..self.d {
-            res.push_str("  ");
+    pub fn with_format(w: W, f: Format) -> SvgIO<W> {
+        SvgIO {
+            w,
+            d: 0,
+            f,
+            deferred: None,
+            ids: Ids::new(),
         }
-        res
     }
 }
 
 impl<W: IOWrite> SvgWrite for SvgIO<W> {
     type Err = std::io::Error;
     fn write(&mut self, s: &str) -> Result<(), Self::Err> {
-        let ps = self.pad();
-        write!(self.w, "{}{}\n", ps, s)
+        if let Some(e) = self.deferred.take() {
+            return Err(e);
+        }
+        let l = self.f.line(self.d, s);
+        self.w.write_all(l.as_bytes())
     }
     fn inc_depth(&mut self, n: i8) {
         self.d += n;
     }
+    fn depth(&self) -> i8 {
+        self.d
+    }
+    fn ids(&mut self) -> Option<&mut Ids> {
+        Some(&mut self.ids)
+    }
+    fn format(&self) -> Format {
+        self.f.clone()
+    }
+    fn defer_err(&mut self, e: Self::Err) {
+        self.deferred.get_or_insert(e);
+    }
+    fn flush(&mut self) -> Result<(), Self::Err> {
+        if let Some(e) = self.deferred.take() {
+            return Err(e);
+        }
+        self.w.flush()
+    }
 }
 
 /// SvgFmt is a very simple writer, that takes an std::fmt::Write aand keeps a tab depth.
@@ -57,30 +87,56 @@ impl<W: IOWrite> SvgWrite for SvgIO<W> {
 pub struct SvgFmt<W: FmtWrite> {
     w: W,
     d: i8,
+    f: Format,
+    deferred: Option<std::fmt::Error>,
+    ids: Ids,
 }
 
 impl<W: FmtWrite> SvgFmt<W> {
     pub fn new(w: W) -> SvgFmt<W> {
-        SvgFmt { w: w, d: 0 }
+        Self::with_format(w, Format::default())
     }
-    fn pad(&self) -> String {
-        let mut res = "".to_string();
-        for _i in 0..self.d {
-            res.push_str("  ");
+    pub fn with_format(w: W, f: Format) -> SvgFmt<W> {
+        SvgFmt {
+            w,
+            d: 0,
+            f,
+            deferred: None,
+            ids: Ids::new(),
         }
-        res
     }
 }
 
 impl<W: FmtWrite> SvgWrite for SvgFmt<W> {
     type Err = std::fmt::Error;
     fn write(&mut self, s: &str) -> Result<(), Self::Err> {
-        let ps = self.pad();
-        write!(self.w, "{}{}\n", ps, s)
+        if let Some(e) = self.deferred.take() {
+            return Err(e);
+        }
+        let l = self.f.line(self.d, s);
+        self.w.write_str(&l)
     }
     fn inc_depth(&mut self, n: i8) {
         self.d += n;
     }
+    fn depth(&self) -> i8 {
+        self.d
+    }
+    fn ids(&mut self) -> Option<&mut Ids> {
+        Some(&mut self.ids)
+    }
+    fn format(&self) -> Format {
+        self.f.clone()
+    }
+    fn defer_err(&mut self, e: Self::Err) {
+        self.deferred.get_or_insert(e);
+    }
+    fn flush(&mut self) -> Result<(), Self::Err> {
+        match self.deferred.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
 }
 
 impl<E> SvgWrite for &mut dyn SvgWrite<Err = E> {
@@ -91,20 +147,91 @@ impl<E> SvgWrite for &mut dyn SvgWrite<Err = E> {
     fn inc_depth(&mut self, n: i8) {
         (*self).inc_depth(n);
     }
+    fn depth(&self) -> i8 {
+        (**self).depth()
+    }
+    fn format(&self) -> Format {
+        (**self).format()
+    }
+    fn defer_err(&mut self, e: E) {
+        (*self).defer_err(e)
+    }
+    fn flush(&mut self) -> Result<(), E> {
+        (*self).flush()
+    }
+    fn resources(&mut self) -> Option<&mut Resources> {
+        (*self).resources()
+    }
+    fn ids(&mut self) -> Option<&mut Ids> {
+        (*self).ids()
+    }
+    fn ns_declared(&self, ns: Ns) -> bool {
+        (**self).ns_declared(ns)
+    }
 }
 
 /// the methods on SvgWrite, do not build any structure
 /// they simply write the output, so if you open something (g or svg) don't forget to close it.
+///
+/// write takes markup and is not escaped, use write_text for plain text content.
 pub trait SvgWrite {
     type Err;
     fn write(&mut self, s: &str) -> Result<(), Self::Err>;
     fn inc_depth(&mut self, n: i8);
+
+    /// the current depth, used by Format::tag to pad arguments put on their own lines
+    fn depth(&self) -> i8 {
+        0
+    }
+
+    /// write plain text, escaping any xml special characters
+    fn write_text(&mut self, s: &str) -> Result<(), Self::Err> {
+        self.write(&escape_text(s))
+    }
+
+    /// the layout used by Tag and Text when writing to this writer
+    fn format(&self) -> Format {
+        Format::default()
+    }
+
+    /// Called with errors that could not be returned, such as a TransWrap failing to
+    /// close when dropped. Writers that can, should keep the error and return it
+    /// from the next write or flush. The default ignores it.
+    fn defer_err(&mut self, _e: Self::Err) {}
+
+    /// Return any deferred error, and flush the underlying writer if it has one.
+    fn flush(&mut self) -> Result<(), Self::Err> {
+        Ok(())
+    }
+
+    /// The registry for shared definitions, if this writer has one, see resource::define
+    fn resources(&mut self) -> Option<&mut Resources> {
+        None
+    }
+
+    /// The ids generated so far in this document, used by resource::define
+    /// to number definitions when there is no registry
+    fn ids(&mut self) -> Option<&mut Ids> {
+        None
+    }
+
+    /// true if an element this writer is inside has declared the namespace
+    fn ns_declared(&self, _ns: Ns) -> bool {
+        false
+    }
 }
 
+/// TransWrap writes "begin" before the first thing written to it, and "end" when it is
+/// finished or dropped. If nothing is written, neither is.
+///
+/// An error closing on drop is passed to the parent writer's defer_err, use finish to
+/// get it directly.
 pub struct TransWrap<'a, E> {
     start: Option<String>,
     td_inc: i8,
     end: String,
+    closed: bool,
+    ns: Vec<Ns>,
     w: &'a mut dyn SvgWrite<Err = E>,
 }
 
@@ -114,13 +241,35 @@ impl<'a, E> TransWrap<'a, E> {
             start: Some(begin.to_string()),
             td_inc: 1,
             end: end.to_string(),
+            closed: false,
+            ns: Vec::new(),
             w,
         }
     }
 
+    /// mark namespaces as declared by "begin", for anything written inside
+    pub fn declaring(mut self, ns: Vec<Ns>) -> Self {
+        self.ns = ns;
+        self
+    }
+
+    /// Close the wrapper, returning any error from writing the end
+    pub fn finish(mut self) -> Result<(), E> {
+        self.close()
+    }
+
+    fn close(&mut self) -> Result<(), E> {
+        if self.start.is_none() && !self.closed {
+            self.closed = true;
+            self.w.inc_depth(-self.td_inc);
+            self.w.write(&self.end)?;
+        }
+        Ok(())
+    }
+
     pub fn force(&mut self) -> Result<(), E> {
         if let Some(ref st) = self.start {
-            self.w.write(&st)?;
+            self.w.write(st)?;
             self.w.inc_depth(1);
             self.start = None;
         }
@@ -138,13 +287,37 @@ impl<'a, E> SvgWrite for TransWrap<'a, E> {
         self.td_inc += n;
         self.w.inc_depth(n);
     }
+    /// one deeper than the parent once begin is written, even if it has not been yet
+    fn depth(&self) -> i8 {
+        match self.start {
+            Some(_) => self.w.depth() + 1,
+            None => self.w.depth(),
+        }
+    }
+    fn format(&self) -> Format {
+        self.w.format()
+    }
+    fn defer_err(&mut self, e: E) {
+        self.w.defer_err(e)
+    }
+    fn flush(&mut self) -> Result<(), E> {
+        self.w.flush()
+    }
+    fn resources(&mut self) -> Option<&mut Resources> {
+        self.w.resources()
+    }
+    fn ids(&mut self) -> Option<&mut Ids> {
+        self.w.ids()
+    }
+    fn ns_declared(&self, ns: Ns) -> bool {
+        self.ns.contains(&ns) || self.w.ns_declared(ns)
+    }
 }
 
 impl<'a, E> Drop for TransWrap<'a, E> {
     fn drop(&mut self) {
-        if self.start == None {
-            self.w.inc_depth(-self.td_inc);
-            self.w.write(&self.end).ok();
+        if let Err(e) = self.close() {
+            self.w.defer_err(e);
         }
     }
 }
@@ -173,6 +346,44 @@ mod test {
         assert_eq!(&buf, "");
     }
 
+    /// Fails any write containing a close tag
+    struct NoClose;
+    impl IOWrite for NoClose {
+        fn write(&mut self, b: &[u8]) -> std::io::Result<usize> {
+            match std::str::from_utf8(b).unwrap().contains("</") {
+                true => Err(std::io::Error::other("full")),
+                false => Ok(b.len()),
+            }
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn test_finish_returns_error() {
+        let mut w = SvgIO::new(NoClose);
+        let mut p = TransWrap::new(&mut w, "<g>", "</g>");
+        p.write("<rect/>").unwrap();
+        assert!(p.finish().is_err());
+        assert!(w.flush().is_ok());
+    }
+
+    #[test]
+    pub fn test_drop_error_deferred() {
+        let mut w = SvgIO::new(NoClose);
+        let mut p = TransWrap::new(&mut w, "<g>", "</g>");
+        p.write("<rect/>").unwrap();
+        drop(p);
+        assert!(w.flush().is_err());
+        assert!(w.flush().is_ok());
+
+        let mut p = TransWrap::new(&mut w, "<g>", "</g>");
+        p.write("<rect/>").unwrap();
+        drop(p);
+        assert!(w.write("<rect/>").is_err());
+    }
+
     #[test]
     pub fn test_force() {
         let mut buf = String::new();